@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// How the characters of a banner are put next to each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FigletLayout {
+    /// Every character keeps its full width.
+    FullWidth,
+    /// Characters are moved together until they touch (kerning).
+    Fitting,
+    /// Characters are moved one column further, overlapping where the given
+    /// rules allow the two characters to merge into one. Each bit enables a
+    /// rule of the FIGfont specification: equal characters (1), underscores
+    /// (2), hierarchy (4), opposite pairs (8), big X (16) and hardblanks
+    /// (32). No rules at all means universal smushing.
+    Smushing(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FigletError {
+    /// The first line is not a `flf2a` header.
+    InvalidHeader(String),
+    /// The file ended before the glyph of `character` was complete.
+    MissingGlyph { character: i64 },
+    /// A code tag line does not start with a character code.
+    InvalidCodeTag(String),
+}
+
+impl fmt::Display for FigletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FigletError::InvalidHeader(line) => write!(f, "invalid FIGfont header: {line:?}"),
+            FigletError::MissingGlyph { character } => {
+                write!(f, "FIGfont ends in the middle of character {character}")
+            }
+            FigletError::InvalidCodeTag(line) => write!(f, "invalid FIGfont code tag: {line:?}"),
+        }
+    }
+}
+
+impl Error for FigletError {}
+
+/// A FIGlet font, parsed from the `.flf` format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FigletFont {
+    hardblank: char,
+    height: usize,
+    layout: FigletLayout,
+    right_to_left: bool,
+    glyphs: HashMap<char, Vec<Vec<char>>>,
+}
+
+const BUNDLED: &str = include_str!("../fonts/block.flf");
+
+/// The characters every FIGfont defines after the printable ASCII range.
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+fn parse_code(code: &str) -> Option<i64> {
+    let (negative, digits) = match code.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, code),
+    };
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn read_glyph<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    height: usize,
+    code: i64,
+) -> Result<Vec<Vec<char>>, FigletError> {
+    (0..height)
+        .map(|_| {
+            let line = lines
+                .next()
+                .ok_or(FigletError::MissingGlyph { character: code })?
+                .trim_end();
+            // Rows end with an end mark, doubled on the last row of a glyph.
+            let row = match line.chars().last() {
+                Some(mark) => {
+                    let row = &line[..line.len() - mark.len_utf8()];
+                    row.strip_suffix(mark).unwrap_or(row)
+                }
+                None => line,
+            };
+            Ok(row.chars().collect())
+        })
+        .collect()
+}
+
+impl FigletFont {
+    /// The font that comes with the crate, a blocky 7 row font.
+    pub fn bundled() -> Self {
+        FigletFont::parse(BUNDLED).expect("the bundled font is valid")
+    }
+
+    pub fn parse(source: &str) -> Result<Self, FigletError> {
+        let mut lines = source.lines();
+        let header = lines.next().unwrap_or_default();
+        let invalid_header = || FigletError::InvalidHeader(header.to_string());
+
+        let signature = header.strip_prefix("flf2a").ok_or_else(invalid_header)?;
+        let hardblank = signature.chars().next().ok_or_else(invalid_header)?;
+        let fields: Vec<i64> = signature[hardblank.len_utf8()..]
+            .split_whitespace()
+            .map(|field| field.parse().map_err(|_| invalid_header()))
+            .collect::<Result<_, _>>()?;
+        let [height, _baseline, _max_length, old_layout, comment_lines, ..] = fields[..] else {
+            return Err(invalid_header());
+        };
+        if height < 1 || comment_lines < 0 {
+            return Err(invalid_header());
+        }
+        let right_to_left = fields.get(5) == Some(&1);
+
+        // The full layout, when present, supersedes the old one.
+        let layout = match fields.get(6) {
+            Some(&full) if full & 128 != 0 => FigletLayout::Smushing((full & 63) as u8),
+            Some(&full) if full & 64 != 0 => FigletLayout::Fitting,
+            Some(_) => FigletLayout::FullWidth,
+            None if old_layout < 0 => FigletLayout::FullWidth,
+            None if old_layout == 0 => FigletLayout::Fitting,
+            None => FigletLayout::Smushing((old_layout & 63) as u8),
+        };
+
+        let mut lines = lines.skip(comment_lines as usize);
+        let height = height as usize;
+        let mut glyphs = HashMap::new();
+        for ch in (' '..='~').chain(DEUTSCH) {
+            match read_glyph(&mut lines, height, ch as i64) {
+                Ok(glyph) => glyphs.insert(ch, glyph),
+                // Plenty of fonts in the wild stop before the Deutsch characters.
+                Err(_) if DEUTSCH.contains(&ch) => break,
+                Err(error) => return Err(error),
+            };
+        }
+
+        // Any further glyphs are introduced by a line giving their code.
+        while let Some(tag) = lines.next() {
+            if tag.trim().is_empty() {
+                continue;
+            }
+            let code = tag.split_whitespace().next().and_then(parse_code);
+            let code = code.ok_or_else(|| FigletError::InvalidCodeTag(tag.to_string()))?;
+            let glyph = read_glyph(&mut lines, height, code)?;
+            // Negative codes name glyphs that no character maps to.
+            if let Some(ch) = u32::try_from(code).ok().and_then(char::from_u32) {
+                glyphs.insert(ch, glyph);
+            }
+        }
+
+        Ok(FigletFont {
+            hardblank,
+            height,
+            layout,
+            right_to_left,
+            glyphs,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The layout the font asks for.
+    pub fn layout(&self) -> FigletLayout {
+        self.layout
+    }
+
+    /// The same font, laid out with `layout` instead of its own.
+    pub fn with_layout(mut self, layout: FigletLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// The rows of `text` written in this font, one block of `height` rows
+    /// per line of `text`. Characters the font lacks are skipped.
+    pub fn render(&self, text: &str) -> Vec<String> {
+        let mut rows = Vec::new();
+        for line in text.split('\n') {
+            let mut output: Vec<Vec<char>> = vec![Vec::new(); self.height];
+            let mut previous_width = 0;
+            for ch in line.chars() {
+                let Some(glyph) = self.glyphs.get(&ch) else {
+                    continue;
+                };
+                let width = glyph.iter().map(Vec::len).max().unwrap_or(0);
+                let mut glyph = glyph.clone();
+                for row in &mut glyph {
+                    row.resize(width, ' ');
+                }
+                if self.right_to_left {
+                    for (row, glyph_row) in output.iter_mut().zip(&mut glyph) {
+                        std::mem::swap(row, glyph_row);
+                    }
+                }
+                self.append(&mut output, &glyph, previous_width.min(width));
+                previous_width = width;
+            }
+            rows.extend(output.into_iter().map(|row| {
+                row.into_iter()
+                    .map(|ch| if ch == self.hardblank { ' ' } else { ch })
+                    .collect()
+            }));
+        }
+        rows
+    }
+
+    /// Adds `glyph` to the right of `output`, overlapping them as far as the
+    /// layout allows. `narrowest` is the width of the narrower of the last
+    /// glyph and this one; glyphs narrower than two columns never smush.
+    fn append(&self, output: &mut [Vec<char>], glyph: &[Vec<char>], narrowest: usize) {
+        let output_width = output.first().map_or(0, Vec::len);
+        let glyph_width = glyph.first().map_or(0, Vec::len);
+
+        let mut overlap = match self.layout {
+            FigletLayout::FullWidth => 0,
+            _ => glyph_width.min(output_width),
+        };
+        if overlap > 0 {
+            for (row, glyph_row) in output.iter().zip(glyph) {
+                let trailing = row.iter().rev().take_while(|&&ch| ch == ' ').count();
+                let leading = glyph_row.iter().take_while(|&&ch| ch == ' ').count();
+                let mut amount = trailing + leading;
+                let left = row.iter().rev().find(|&&ch| ch != ' ');
+                let right = glyph_row.iter().find(|&&ch| ch != ' ');
+                if let (Some(&left), Some(&right)) = (left, right) {
+                    if self.smush(left, right, narrowest).is_some() {
+                        amount += 1;
+                    }
+                }
+                overlap = overlap.min(amount);
+            }
+        }
+
+        for (row, glyph_row) in output.iter_mut().zip(glyph) {
+            let start = row.len() - overlap;
+            for (i, &ch) in glyph_row.iter().enumerate() {
+                match row.get_mut(start + i) {
+                    Some(existing) => {
+                        *existing = match (*existing, ch) {
+                            (' ', ch) => ch,
+                            (existing, ' ') => existing,
+                            (existing, ch) => self.smush(existing, ch, narrowest).unwrap_or(ch),
+                        }
+                    }
+                    None => row.push(ch),
+                }
+            }
+        }
+    }
+
+    /// The character that `left` and `right` merge into when they overlap,
+    /// if the layout lets them.
+    fn smush(&self, left: char, right: char, narrowest: usize) -> Option<char> {
+        let FigletLayout::Smushing(rules) = self.layout else {
+            return None;
+        };
+        if narrowest < 2 {
+            return None;
+        }
+        let hardblank = self.hardblank;
+
+        if rules & 63 == 0 {
+            // Universal smushing: the character in front wins.
+            return Some(match (left == hardblank, right == hardblank) {
+                (true, _) => right,
+                (_, true) => left,
+                _ if self.right_to_left => left,
+                _ => right,
+            });
+        }
+
+        if left == hardblank || right == hardblank {
+            return (rules & 32 != 0 && left == right).then_some(left);
+        }
+        if rules & 1 != 0 && left == right {
+            return Some(left);
+        }
+        const BORDERS: &str = "|/\\[]{}()<>";
+        if rules & 2 != 0 {
+            if left == '_' && BORDERS.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && BORDERS.contains(left) {
+                return Some(left);
+            }
+        }
+        if rules & 4 != 0 {
+            const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |ch: char| CLASSES.iter().position(|class| class.contains(ch));
+            match (class(left), class(right)) {
+                (Some(l), Some(r)) if l > r => return Some(left),
+                (Some(l), Some(r)) if l < r => return Some(right),
+                _ => {}
+            }
+        }
+        if rules & 8 != 0 {
+            if let "[]" | "][" | "{}" | "}{" | "()" | ")(" = format!("{left}{right}").as_str() {
+                return Some('|');
+            }
+        }
+        if rules & 16 != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl Default for FigletFont {
+    fn default() -> Self {
+        FigletFont::bundled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one row font with the given header and glyphs, and "?" for the
+    /// other required characters.
+    fn font(header: &str, glyphs: &[(char, &str)]) -> String {
+        let mut source = format!("{header}\n");
+        for ch in ' '..='~' {
+            let glyph = match glyphs.iter().find(|(glyph_ch, _)| *glyph_ch == ch) {
+                Some((_, glyph)) => glyph,
+                None if ch == ' ' => "$",
+                None => "?",
+            };
+            source.push_str(&format!("{glyph}@@\n"));
+        }
+        source
+    }
+
+    fn render(header: &str, glyphs: &[(char, &str)], text: &str) -> Vec<String> {
+        FigletFont::parse(&font(header, glyphs))
+            .unwrap()
+            .render(text)
+    }
+
+    #[test]
+    fn parses_the_layout_from_the_header() {
+        let layout = |header: &str| FigletFont::parse(&font(header, &[])).unwrap().layout();
+        assert_eq!(layout("flf2a$ 1 1 2 -1 0"), FigletLayout::FullWidth);
+        assert_eq!(layout("flf2a$ 1 1 2 0 0"), FigletLayout::Fitting);
+        assert_eq!(layout("flf2a$ 1 1 2 15 0"), FigletLayout::Smushing(15));
+        // The full layout wins over the old one.
+        assert_eq!(layout("flf2a$ 1 1 2 15 0 0 0"), FigletLayout::FullWidth);
+        assert_eq!(layout("flf2a$ 1 1 2 -1 0 0 64"), FigletLayout::Fitting);
+        assert_eq!(layout("flf2a$ 1 1 2 -1 0 0 133"), FigletLayout::Smushing(5));
+    }
+
+    #[test]
+    fn parses_comments_deutsch_and_code_tagged_glyphs() {
+        let mut source = font("flf2a$ 1 1 2 -1 2", &[('a', "A")]);
+        source.insert_str(source.find('\n').unwrap() + 1, "a comment\nanother one\n");
+        for glyph in ["Ä", "Ö", "Ü", "ä", "ö", "ü", "ß"] {
+            source.push_str(&format!("{glyph}{glyph}@@\n"));
+        }
+        source.push_str("0x263A smiley\n:)@@\n\n0101 octal A\nO@@\n-2 unmapped\nx@@\n");
+        let parsed = FigletFont::parse(&source).unwrap();
+        assert_eq!(parsed.render("aäß☺A"), ["Aääßß:)O"]);
+
+        // Fonts that stop before the Deutsch glyphs are fine too.
+        let parsed = FigletFont::parse(&font("flf2a$ 1 1 2 -1 0", &[])).unwrap();
+        assert_eq!(parsed.render("ab"), ["??"]);
+    }
+
+    #[test]
+    fn reports_broken_fonts() {
+        assert!(matches!(
+            FigletFont::parse("tlf2a$ 1 1 2 -1 0"),
+            Err(FigletError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            FigletFont::parse("flf2a$ 1 1"),
+            Err(FigletError::InvalidHeader(_))
+        ));
+        assert_eq!(
+            FigletFont::parse("flf2a$ 2 1 2 -1 0\n$@\n$@@\n!@\n"),
+            Err(FigletError::MissingGlyph { character: 33 })
+        );
+        let mut source = font("flf2a$ 1 1 2 -1 0", &[]);
+        source.push_str(&"?@@\n".repeat(DEUTSCH.len()));
+        source.push_str("smiley\n:)@@\n");
+        assert_eq!(
+            FigletFont::parse(&source),
+            Err(FigletError::InvalidCodeTag("smiley".to_string()))
+        );
+    }
+
+    #[test]
+    fn strips_at_most_two_end_marks() {
+        let source = "flf2a$ 2 1 4 -1 0\n".to_string()
+            + &(' '..='~')
+                .map(|ch| match ch {
+                    '#' => "a###\nb###\r\n".to_string(),
+                    _ => "x#\nx##\n".to_string(),
+                })
+                .collect::<String>();
+        let font = FigletFont::parse(&source).unwrap();
+        assert_eq!(font.render("#"), ["a#", "b#"]);
+        assert_eq!(font.render("!"), ["x", "x"]);
+    }
+
+    #[test]
+    fn lays_characters_out_full_width_or_fitted() {
+        let glyphs = [('p', "p "), ('q', " q"), ('n', "n$"), ('o', "$o")];
+        assert_eq!(render("flf2a$ 1 1 2 -1 0", &glyphs, "pqno"), ["p  qn  o"]);
+        assert_eq!(render("flf2a$ 1 1 2 0 0", &glyphs, "pqno"), ["pqn  o"]);
+        assert_eq!(
+            render("flf2a$ 1 1 2 0 0", &glyphs, "pq\nqp"),
+            ["pq", " qp "]
+        );
+    }
+
+    #[test]
+    fn smushes_by_the_enabled_rules() {
+        let glyphs = [
+            ('a', "a|"),
+            ('b', "|b"),
+            ('c', "c/"),
+            ('e', "e["),
+            ('f', "]f"),
+            ('g', "g/"),
+            ('h', "\\h"),
+            ('i', "i\\"),
+            ('j', "/j"),
+            ('k', "k>"),
+            ('l', "<l"),
+            ('m', "m_"),
+            ('n', "n$"),
+            ('o', "$o"),
+            ('1', "|"),
+        ];
+        let smush = |rules: u8, text: &str| {
+            render(&format!("flf2a$ 1 1 2 {rules} 0"), &glyphs, text).remove(0)
+        };
+        let universal = |text: &str| render("flf2a$ 1 1 2 -1 0 0 128", &glyphs, text).remove(0);
+
+        assert_eq!(smush(1, "ab"), "a|b");
+        assert_eq!(smush(2, "mb"), "m|b");
+        assert_eq!(smush(4, "cb"), "c/b");
+        assert_eq!(smush(8, "ef"), "e|f");
+        assert_eq!(smush(16, "gh"), "g|h");
+        assert_eq!(smush(16, "ij"), "iYj");
+        assert_eq!(smush(16, "kl"), "kXl");
+        assert_eq!(smush(32, "no"), "n o");
+
+        // Without a matching rule the characters only touch.
+        assert_eq!(smush(1, "cb"), "c/|b");
+        assert_eq!(smush(31, "no"), "n  o");
+        // Characters narrower than two columns are never smushed.
+        assert_eq!(smush(1, "11"), "||");
+
+        assert_eq!(universal("cb"), "c|b");
+        assert_eq!(universal("no"), "n o");
+    }
+
+    #[test]
+    fn renders_right_to_left_fonts_backwards() {
+        let glyphs = [('a', "a|"), ('b', "|b")];
+        assert_eq!(render("flf2a$ 1 1 2 -1 0 1", &glyphs, "ab"), ["|ba|"]);
+    }
+
+    #[test]
+    fn bundled_font_renders() {
+        let font = FigletFont::bundled();
+        assert_eq!(font.height(), 7);
+        assert_eq!(font.layout(), FigletLayout::Fitting);
+
+        let rows = font.render("Hi!\nÄß");
+        assert_eq!(rows.len(), 14);
+        assert!(rows.iter().all(|row| !row.contains('$')));
+        assert!(rows[..7].iter().all(|row| row.len() == rows[0].len()));
+        assert!(rows[7..].iter().any(|row| row.contains('#')));
+    }
+}
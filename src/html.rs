@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::svg::escape_xml;
+use crate::{AsciiCanvas, Style};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlOptions {
+    /// Wrap styled cells in spans carrying their colors and attributes.
+    pub color: bool,
+    /// Give the coordinate labels written by `AsciiDrawer::rect_with_labels`
+    /// a `title` tooltip with the exact world coordinates. Ignored when
+    /// exporting a bare canvas.
+    pub coordinate_tooltips: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            color: true,
+            coordinate_tooltips: true,
+        }
+    }
+}
+
+fn inline_style(style: &Style) -> String {
+    let mut css = String::new();
+    if let Some(fg) = style.fg {
+        write!(css, "color:{};", fg.to_hex()).unwrap();
+    }
+    if let Some(bg) = style.bg {
+        write!(css, "background-color:{};", bg.to_hex()).unwrap();
+    }
+    if style.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if style.dim {
+        css.push_str("opacity:0.5;");
+    }
+    if style.underline {
+        css.push_str("text-decoration:underline;");
+    }
+    css
+}
+
+pub(crate) fn render(
+    canvas: &AsciiCanvas,
+    options: &HtmlOptions,
+    tooltips: &HashMap<(i32, i32), String>,
+) -> String {
+    let frame = canvas.frame();
+    let mut html = String::from("<pre style=\"font-family:monospace;line-height:1.2\">");
+
+    for (row_index, row) in canvas.rows().iter().enumerate() {
+        let y = frame.top - row_index as i32;
+        let span_key = |column: usize| {
+            let style = if options.color {
+                row[column].style
+            } else {
+                Style::default()
+            };
+            (style, tooltips.get(&(frame.left + column as i32, y)))
+        };
+
+        let mut column = 0;
+        while column < row.len() {
+            let key = span_key(column);
+            let mut text = String::new();
+            while column < row.len() && span_key(column) == key {
+                row[column].push_to(&mut text);
+                column += 1;
+            }
+
+            let (style, title) = key;
+            let css = inline_style(&style);
+            if css.is_empty() && title.is_none() {
+                html.push_str(&escape_xml(&text));
+                continue;
+            }
+
+            html.push_str("<span");
+            if !css.is_empty() {
+                write!(html, " style=\"{css}\"").unwrap();
+            }
+            if let Some(title) = title {
+                write!(html, " title=\"{}\"", escape_xml(title)).unwrap();
+            }
+            write!(html, ">{}</span>", escape_xml(&text)).unwrap();
+        }
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n");
+    html
+}
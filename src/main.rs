@@ -1,11 +1,78 @@
 #![allow(dead_code)]
 
+mod figlet;
+mod html;
+mod png;
+mod style;
+mod svg;
+mod terminal;
+mod unicode;
+
+use std::cell::OnceCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+pub use figlet::{FigletError, FigletFont, FigletLayout};
+pub use html::HtmlOptions;
+pub use png::PngOptions;
+pub use style::{Color, Style};
+pub use svg::SvgOptions;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct Vec2 {
-    x: f32,
-    y: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn dot(self, rhs: Vec2) -> f32 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    /// The z component of the 3D cross product, positive when `rhs` is
+    /// counter-clockwise from `self`.
+    pub fn cross(self, rhs: Vec2) -> f32 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// The vector scaled to length 1, or zero for the zero vector.
+    pub fn normalize(self) -> Vec2 {
+        let length = self.length();
+        if length > 0.0 {
+            self / length
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// The point a fraction `t` of the way from `self` to `rhs`.
+    pub fn lerp(self, rhs: Vec2, t: f32) -> Vec2 {
+        self + (rhs - self) * t
+    }
+
+    /// Rotates counter-clockwise by `angle` radians around the origin.
+    pub fn rotate(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2 {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
 }
 
 impl From<[f32; 2]> for Vec2 {
@@ -17,8 +84,24 @@ impl From<[f32; 2]> for Vec2 {
     }
 }
 
-use std::ops::Mul;
+impl From<(f32, f32)> for Vec2 {
+    fn from((x, y): (f32, f32)) -> Self {
+        Vec2 { x, y }
+    }
+}
+
+impl From<[i32; 2]> for Vec2 {
+    fn from(arr: [i32; 2]) -> Self {
+        Vec2 {
+            x: arr[0] as f32,
+            y: arr[1] as f32,
+        }
+    }
+}
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
+/// Component-wise product.
 impl Mul<Vec2> for Vec2 {
     type Output = Vec2;
 
@@ -30,6 +113,61 @@ impl Mul<Vec2> for Vec2 {
     }
 }
 
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, rhs: f32) -> Vec2 {
+        Vec2 {
+            x: self.x / rhs,
+            y: self.y / rhs,
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2 {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
 trait AsciiVec2Ext {
     fn to_string(&self) -> String;
 }
@@ -40,7 +178,9 @@ impl AsciiVec2Ext for Vec2 {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
+/// An axis-aligned box, inclusive of its edges. A box whose minimum is
+/// greater than its maximum on either axis is empty.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct AABB {
     pub x_min: f32,
     pub x_max: f32,
@@ -49,7 +189,115 @@ pub struct AABB {
 }
 
 impl AABB {
-    fn include_point(&mut self, point: Vec2) {
+    /// Contains nothing; including a point in it gives a box around just that point.
+    pub const EMPTY: AABB = AABB {
+        x_min: f32::INFINITY,
+        x_max: f32::NEG_INFINITY,
+        y_min: f32::INFINITY,
+        y_max: f32::NEG_INFINITY,
+    };
+
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        AABB {
+            x_min: min.x,
+            x_max: max.x,
+            y_min: min.y,
+            y_max: max.y,
+        }
+    }
+
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half = Vec2::new(size.x.abs(), size.y.abs()) / 2.0;
+        AABB::new(center - half, center + half)
+    }
+
+    /// The smallest box containing all of `points`.
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut aabb = AABB::EMPTY;
+        for point in points {
+            aabb.include_point(point);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x_min > self.x_max || self.y_min > self.y_max
+    }
+
+    pub fn min(&self) -> Vec2 {
+        Vec2::new(self.x_min, self.y_min)
+    }
+
+    pub fn max(&self) -> Vec2 {
+        Vec2::new(self.x_max, self.y_max)
+    }
+
+    /// The middle of the box, or zero for an empty box.
+    pub fn center(&self) -> Vec2 {
+        if self.is_empty() {
+            Vec2::ZERO
+        } else {
+            (self.min() + self.max()) / 2.0
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        if self.is_empty() {
+            Vec2::ZERO
+        } else {
+            self.max() - self.min()
+        }
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &AABB) -> AABB {
+        AABB {
+            x_min: self.x_min.min(other.x_min),
+            x_max: self.x_max.max(other.x_max),
+            y_min: self.y_min.min(other.y_min),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+
+    /// The region covered by both boxes, possibly empty.
+    pub fn intersection(&self, other: &AABB) -> AABB {
+        let intersection = AABB {
+            x_min: self.x_min.max(other.x_min),
+            x_max: self.x_max.min(other.x_max),
+            y_min: self.y_min.max(other.y_min),
+            y_max: self.y_max.min(other.y_max),
+        };
+        if intersection.is_empty() {
+            AABB::EMPTY
+        } else {
+            intersection
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        (self.x_min..=self.x_max).contains(&point.x) && (self.y_min..=self.y_max).contains(&point.y)
+    }
+
+    /// Whether the boxes share at least one point, edges included.
+    pub fn overlaps(&self, other: &AABB) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Grows the box by `amount` on every side, or shrinks it for a negative
+    /// `amount`. An empty box stays empty.
+    pub fn expand(&self, amount: f32) -> AABB {
+        if self.is_empty() {
+            return AABB::EMPTY;
+        }
+        AABB {
+            x_min: self.x_min - amount,
+            x_max: self.x_max + amount,
+            y_min: self.y_min - amount,
+            y_max: self.y_max + amount,
+        }
+    }
+
+    pub fn include_point(&mut self, point: Vec2) {
         if point.x < self.x_min {
             self.x_min = point.x;
         }
@@ -65,172 +313,2537 @@ impl AABB {
     }
 }
 
-pub struct AsciiCanvas {
-    buffer: HashMap<(i32, i32), char>,
-    bounds: AABB,
+impl Default for AABB {
+    fn default() -> Self {
+        AABB::EMPTY
+    }
 }
 
-impl AsciiCanvas {
-    pub fn new() -> Self {
-        AsciiCanvas {
-            buffer: HashMap::new(),
-            bounds: AABB::default(),
+/// A 2x3 affine transform, mapping `(x, y)` to
+/// `(m[0][0] x + m[0][1] y + m[0][2], m[1][0] x + m[1][1] y + m[1][2])`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub matrix: [[f32; 3]; 2],
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    };
+
+    pub fn translation(offset: Vec2) -> Self {
+        Transform {
+            matrix: [[1.0, 0.0, offset.x], [0.0, 1.0, offset.y]],
         }
     }
 
-    pub fn rect(&mut self, center: Vec2, size: Vec2) -> &mut Self {
-        let half_width = (size.x / 2.0).ceil() as i32;
-        let half_height = (size.y / 2.0).ceil() as i32;
-        let center_x = center.x.round() as i32;
-        let center_y = center.y.round() as i32;
+    /// Counter-clockwise rotation by `angle` radians around the origin.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform {
+            matrix: [[cos, -sin, 0.0], [sin, cos, 0.0]],
+        }
+    }
 
-        for x in -half_width..=half_width {
-            let px = center_x + x;
-            let py_top = center_y - half_height;
-            let py_bottom = center_y + half_height;
-            self.buffer.insert((px, py_top), '-');
-            self.buffer.insert((px, py_bottom), '-');
-            self.bounds.include_point([px as f32, py_top as f32].into());
-            self.bounds
-                .include_point([px as f32, py_bottom as f32].into());
+    pub fn scaling(factors: Vec2) -> Self {
+        Transform {
+            matrix: [[factors.x, 0.0, 0.0], [0.0, factors.y, 0.0]],
         }
+    }
 
-        for y in -half_height..=half_height {
-            let py = center_y + y;
-            let px_left = center_x - half_width;
-            let px_right = center_x + half_width;
-            self.buffer.insert((px_left, py), '|');
-            self.buffer.insert((px_right, py), '|');
-            self.bounds
-                .include_point([px_left as f32, py as f32].into());
-            self.bounds
-                .include_point([px_right as f32, py as f32].into());
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        let m = &self.matrix;
+        Vec2 {
+            x: m[0][0] * point.x + m[0][1] * point.y + m[0][2],
+            y: m[1][0] * point.x + m[1][1] * point.y + m[1][2],
         }
+    }
 
-        self
+    /// Whether the transform keeps axis-aligned rectangles axis-aligned.
+    fn is_axis_aligned(&self) -> bool {
+        self.matrix[0][1] == 0.0 && self.matrix[1][0] == 0.0
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
     }
+}
 
-    pub fn text(&mut self, position: Vec2, text: &str) -> &mut Self {
-        let start_x = position.x.round() as i32 - (text.len() as i32 / 2);
-        let start_y = position.y.round() as i32;
+/// `a * b` applies `b` first, then `a`.
+impl Mul<Transform> for Transform {
+    type Output = Transform;
 
-        for (i, ch) in text.chars().enumerate() {
-            let x = start_x + i as i32;
-            self.buffer.insert((x, start_y), ch);
-            self.bounds.include_point([x as f32, start_y as f32].into());
+    fn mul(self, rhs: Transform) -> Transform {
+        let (a, b) = (&self.matrix, &rhs.matrix);
+        let mut matrix = [[0.0; 3]; 2];
+        for row in 0..2 {
+            for column in 0..3 {
+                matrix[row][column] = a[row][0] * b[0][column] + a[row][1] * b[1][column];
+            }
+            matrix[row][2] += a[row][2];
         }
+        Transform { matrix }
+    }
+}
 
-        self
+const STROKE_UP: u8 = 1 << 0;
+const STROKE_DOWN: u8 = 1 << 1;
+const STROKE_LEFT: u8 = 1 << 2;
+const STROKE_RIGHT: u8 = 1 << 3;
+const STROKE_UP_RIGHT: u8 = 1 << 4;
+const STROKE_DOWN_LEFT: u8 = 1 << 5;
+const STROKE_UP_LEFT: u8 = 1 << 6;
+const STROKE_DOWN_RIGHT: u8 = 1 << 7;
+
+const STROKE_RISING: u8 = STROKE_UP_RIGHT | STROKE_DOWN_LEFT;
+const STROKE_FALLING: u8 = STROKE_UP_LEFT | STROKE_DOWN_RIGHT;
+const STROKE_VERTICAL: u8 = STROKE_UP | STROKE_DOWN;
+const STROKE_HORIZONTAL: u8 = STROKE_LEFT | STROKE_RIGHT;
+const STROKE_ORTHOGONAL: u8 = STROKE_VERTICAL | STROKE_HORIZONTAL;
+const STROKE_DIAGONAL: u8 = STROKE_RISING | STROKE_FALLING;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineStyle {
+    #[default]
+    Ascii,
+    Light,
+    Heavy,
+    Double,
+    Rounded,
+}
+
+impl LineStyle {
+    /// Glyph for a cell whose strokes leave it in the directions set in `strokes`.
+    fn glyph(self, strokes: u8) -> char {
+        if strokes & STROKE_DIAGONAL != 0 {
+            let (rising, falling) = (strokes & STROKE_RISING, strokes & STROKE_FALLING);
+            // Only two whole diagonals cross. Where the ends of two meet, the
+            // glyph points at the corner they make.
+            let apex = match rising | falling {
+                s if s == STROKE_DOWN_LEFT | STROKE_DOWN_RIGHT => Some('^'),
+                s if s == STROKE_UP_LEFT | STROKE_UP_RIGHT => Some('v'),
+                s if s == STROKE_UP_RIGHT | STROKE_DOWN_RIGHT => Some('<'),
+                s if s == STROKE_UP_LEFT | STROKE_DOWN_LEFT => Some('>'),
+                _ => None,
+            };
+            if let Some(apex) = apex {
+                return apex;
+            }
+            let crossed = rising == STROKE_RISING && falling == STROKE_FALLING;
+            let rising = rising == STROKE_RISING || falling == 0;
+            return match (self, crossed, rising) {
+                (LineStyle::Ascii, true, _) => 'X',
+                (LineStyle::Ascii, false, true) => '/',
+                (LineStyle::Ascii, false, false) => '\\',
+                (_, true, _) => '╳',
+                (_, false, true) => '╱',
+                (_, false, false) => '╲',
+            };
+        }
+
+        // A stroke that only leaves on one side is drawn as a full segment.
+        let mut strokes = strokes & STROKE_ORTHOGONAL;
+        if strokes & STROKE_HORIZONTAL == 0 && strokes != 0 {
+            strokes = STROKE_VERTICAL;
+        } else if strokes & STROKE_VERTICAL == 0 && strokes != 0 {
+            strokes = STROKE_HORIZONTAL;
+        }
+
+        let glyphs = match self {
+            LineStyle::Ascii => ['|', '-', '+', '+', '+', '+', '+', '+', '+', '+', '+'],
+            LineStyle::Light => ['│', '─', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼'],
+            LineStyle::Heavy => ['┃', '━', '┏', '┓', '┗', '┛', '┣', '┫', '┳', '┻', '╋'],
+            LineStyle::Double => ['║', '═', '╔', '╗', '╚', '╝', '╠', '╣', '╦', '╩', '╬'],
+            LineStyle::Rounded => ['│', '─', '╭', '╮', '╰', '╯', '├', '┤', '┬', '┴', '┼'],
+        };
+        let index = match strokes {
+            STROKE_VERTICAL => 0,
+            STROKE_HORIZONTAL => 1,
+            s if s == STROKE_DOWN | STROKE_RIGHT => 2,
+            s if s == STROKE_DOWN | STROKE_LEFT => 3,
+            s if s == STROKE_UP | STROKE_RIGHT => 4,
+            s if s == STROKE_UP | STROKE_LEFT => 5,
+            s if s == STROKE_VERTICAL | STROKE_RIGHT => 6,
+            s if s == STROKE_VERTICAL | STROKE_LEFT => 7,
+            s if s == STROKE_HORIZONTAL | STROKE_DOWN => 8,
+            s if s == STROKE_HORIZONTAL | STROKE_UP => 9,
+            _ => 10,
+        };
+        glyphs[index]
     }
+}
 
-    pub fn draw(&self) {
-        let width = (self.bounds.x_max - self.bounds.x_min).ceil() as i32 + 1;
-        let height = (self.bounds.y_max - self.bounds.y_min).ceil() as i32 + 1;
-        let offset_x = self.bounds.x_min.floor() as i32;
-        let offset_y = self.bounds.y_min.floor() as i32;
+/// What happens when a stroke is written over a cell that already holds one.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The new stroke replaces whatever was there.
+    Overwrite,
+    /// Crossing strokes are joined into the matching junction glyph.
+    #[default]
+    Merge,
+}
+
+// Terminal cells are roughly twice as tall as they are wide.
+const CELL_ASPECT: f32 = 2.0;
+
+/// Picks the strokes that best follow a line going in direction (dx, dy),
+/// measured in cells that are `aspect_ratio` times taller than wide.
+fn slope_strokes(dx: f32, dy: f32, aspect_ratio: f32) -> u8 {
+    let (dx, dy) = (dx.abs(), dy * dx.signum() * aspect_ratio);
+    if dy.abs() <= dx * 0.4142 {
+        STROKE_HORIZONTAL
+    } else if dy.abs() >= dx * 2.4142 {
+        STROKE_VERTICAL
+    } else if dy > 0.0 {
+        STROKE_RISING
+    } else {
+        STROKE_FALLING
+    }
+}
 
-        let mut canvas = vec![vec![' '; width as usize]; height as usize];
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    ch: char,
+    /// The whole grapheme when `ch` carries combining marks or is joined to
+    /// other characters.
+    cluster: Option<Box<str>>,
+    strokes: u8,
+    style: Style,
+    /// Hides the layers below even when blank, like the gaps between words.
+    opaque: bool,
+}
+
+/// Fills the cell to the right of a wide character, which the terminal
+/// draws over it.
+const WIDE_CONTINUATION: char = '\0';
 
-        for (&(x, y), &ch) in &self.buffer {
-            let canvas_x = (x - offset_x) as usize;
-            let canvas_y = (y - offset_y) as usize;
-            canvas[canvas_y][canvas_x] = ch;
+impl Cell {
+    /// Appends what the terminal should be sent for this cell.
+    fn push_to(&self, out: &mut String) {
+        match (&self.cluster, self.ch) {
+            (_, WIDE_CONTINUATION) => {}
+            (Some(cluster), _) => out.push_str(cluster),
+            (None, ch) => out.push(ch),
         }
+    }
+}
 
-        // Disable line wrapping
-        print!("\x1B[?7l");
+const EMPTY_CELL: Cell = Cell {
+    ch: ' ',
+    cluster: None,
+    strokes: 0,
+    opaque: false,
+    style: Style {
+        fg: None,
+        bg: None,
+        bold: false,
+        dim: false,
+        underline: false,
+    },
+};
+
+/// A number of cells on each side of a rectangle.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Spacing {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl Spacing {
+    pub fn uniform(cells: u32) -> Self {
+        Spacing::symmetric(cells, cells)
+    }
 
-        for row in canvas.iter().rev() {
-            println!("{}", row.iter().collect::<String>());
+    pub fn symmetric(horizontal: u32, vertical: u32) -> Self {
+        Spacing {
+            left: horizontal,
+            right: horizontal,
+            top: vertical,
+            bottom: vertical,
         }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Wrap the output in the escape codes that stop terminals from wrapping long rows.
+    pub disable_line_wrap: bool,
+    /// Emit SGR escape codes for cell colors and attributes, plain text otherwise.
+    pub color: bool,
+    /// Blank lines and columns written around the drawing, outside of it.
+    /// Unlike [`AsciiCanvas::set_padding`] it only affects text output.
+    pub margin: Spacing,
+}
 
-        // Enable line wrapping
-        print!("\x1B[?7h");
+impl RenderOptions {
+    /// Options suited for printing straight to a terminal.
+    pub fn terminal() -> Self {
+        RenderOptions {
+            disable_line_wrap: true,
+            color: true,
+            ..RenderOptions::default()
+        }
     }
 }
 
-pub struct AsciiDrawer {
-    canvas: AsciiCanvas,
-    scale: Vec2,
+/// An inclusive range of cells.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CellRect {
+    left: i32,
+    right: i32,
+    bottom: i32,
+    top: i32,
 }
 
-impl AsciiDrawer {
-    pub fn new() -> Self {
-        AsciiDrawer {
-            canvas: AsciiCanvas::new(),
-            scale: [1., 1.].into(),
+impl CellRect {
+    fn width(&self) -> usize {
+        (self.right - self.left + 1).max(0) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.top - self.bottom + 1).max(0) as usize
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.left..=self.right).contains(&x) && (self.bottom..=self.top).contains(&y)
+    }
+}
+
+/// The cells whose centers the outline of a rect passes through.
+fn rect_frame(center: Vec2, size: Vec2) -> CellRect {
+    let half_width = (size.x / 2.0).ceil() as i32;
+    let half_height = (size.y / 2.0).ceil() as i32;
+    let center_x = center.x.round() as i32;
+    let center_y = center.y.round() as i32;
+
+    CellRect {
+        left: center_x - half_width,
+        right: center_x + half_width,
+        bottom: center_y - half_height,
+        top: center_y + half_height,
+    }
+}
+
+/// Which part of a text goes at the position it is written at, horizontally.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HorizontalAnchor {
+    /// The text starts at the position.
+    Left,
+    #[default]
+    Center,
+    /// The text ends at the position.
+    Right,
+}
+
+/// Which line of a multi-line text goes at the position it is written at.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VerticalAnchor {
+    /// The first line, the others go below it.
+    Top,
+    /// The middle line, or the upper of the two middle ones.
+    #[default]
+    Middle,
+    /// The last line, the others go above it.
+    Bottom,
+}
+
+/// How `text` places its lines, which are separated by `\n`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextLayout {
+    /// Where each line sits relative to the position, every line being
+    /// aligned on its own.
+    pub horizontal: HorizontalAnchor,
+    pub vertical: VerticalAnchor,
+    /// Blank rows left between consecutive lines.
+    pub line_spacing: u32,
+}
+
+impl TextLayout {
+    pub fn new(horizontal: HorizontalAnchor, vertical: VerticalAnchor) -> Self {
+        TextLayout {
+            horizontal,
+            vertical,
+            line_spacing: 0,
         }
     }
+}
 
-    pub fn with_scale(scale: Vec2) -> Self {
-        AsciiDrawer {
-            canvas: AsciiCanvas::new(),
-            scale,
+/// The cell each grapheme of `text` starts at when written at `position`.
+/// Wide graphemes also cover the cell to their right.
+fn layout_text<'a>(
+    position: Vec2,
+    text: &'a str,
+    layout: &TextLayout,
+) -> Vec<((i32, i32), &'a str)> {
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let step = 1 + layout.line_spacing as i32;
+    let height = (lines.len() as i32 - 1) * step;
+    let top = position.y.round() as i32
+        + match layout.vertical {
+            VerticalAnchor::Top => 0,
+            VerticalAnchor::Middle => height / 2,
+            VerticalAnchor::Bottom => height,
+        };
+
+    let mut cells = Vec::new();
+    for (i, line) in lines.into_iter().enumerate() {
+        let width = unicode::text_width(line) as i32;
+        let mut x = position.x.round() as i32
+            - match layout.horizontal {
+                HorizontalAnchor::Left => 0,
+                HorizontalAnchor::Center => width / 2,
+                HorizontalAnchor::Right => width - 1,
+            };
+        // Rows are numbered upwards, so each line goes one step below the last.
+        let y = top - i as i32 * step;
+        for cluster in unicode::graphemes(line) {
+            cells.push(((x, y), cluster));
+            x += unicode::cluster_width(cluster) as i32;
         }
     }
+    cells
+}
 
-    pub fn rect(&mut self, center: Vec2, size: Vec2) -> &mut Self {
-        let scaled_center: Vec2 = center * self.scale;
-        let scaled_size: Vec2 = size * self.scale;
-        self.canvas.rect(scaled_center, scaled_size);
-        self
+const ELLIPSIS: &str = "...";
+
+/// Breaks `text` into lines at most `width` cells wide, between words where
+/// possible and hyphenating words that are wider than a whole line. Lines
+/// past `max_lines` are dropped, and the last one kept ends in an ellipsis.
+fn wrap_text(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if width == 0 || max_lines == 0 {
+        return lines;
     }
 
-    pub fn text(&mut self, position: Vec2, text: &str) -> &mut Self {
-        let scaled_position: Vec2 = position * self.scale;
-        self.canvas.text(scaled_position, text);
-        self
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = unicode::text_width(word);
+            if line_width > 0 && line_width + 1 + word_width <= width {
+                line.push(' ');
+                line.push_str(word);
+                line_width += 1 + word_width;
+                continue;
+            }
+            if line_width > 0 {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            if word_width <= width {
+                line.push_str(word);
+                line_width = word_width;
+                continue;
+            }
+
+            // Too long for any line: fill whole lines, ending each with a hyphen.
+            let hyphen = usize::from(width > 1);
+            for cluster in unicode::graphemes(word) {
+                let cluster_width = unicode::cluster_width(cluster);
+                if line_width > 0 && line_width + cluster_width + hyphen > width {
+                    if hyphen == 1 {
+                        line.push('-');
+                    }
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push_str(cluster);
+                line_width += cluster_width;
+            }
+        }
+        lines.push(line);
     }
 
-    pub fn rect_with_labels(
-        &mut self,
-        center: Vec2,
-        size: Vec2,
-        corners_coords: bool,
-        center_coords: bool,
-        edge_lengths: bool,
-    ) -> &mut Self {
-        self.rect(center, size);
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        let last = lines.last_mut().expect("max_lines is not zero");
+        let room = width.saturating_sub(ELLIPSIS.len());
+        let mut kept = String::new();
+        let mut kept_width = 0;
+        for cluster in unicode::graphemes(last.trim_end()) {
+            kept_width += unicode::cluster_width(cluster);
+            if kept_width > room {
+                break;
+            }
+            kept.push_str(cluster);
+        }
+        kept.push_str(&ELLIPSIS[..ELLIPSIS.len().min(width)]);
+        *last = kept;
+    }
+    lines
+}
 
-        let half_width = size.x / 2.0;
-        let half_height = size.y / 2.0;
+/// Which way +y points in the rendered output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum YAxis {
+    /// +y points up, as in physics and maths.
+    #[default]
+    Up,
+    /// +y points down, as in screen space.
+    Down,
+}
 
-        let corners = [
-            [center.x - half_width, center.y - half_height].into(),
-            [center.x + half_width, center.y - half_height].into(),
-            [center.x - half_width, center.y + half_height].into(),
-            [center.x + half_width, center.y + half_height].into(),
-        ];
+const DEFAULT_FILL_CHAR: char = '#';
+const POINT_CHAR: char = '*';
 
-        if corners_coords {
-            for corner in &corners {
-                self.text(*corner, &corner.to_string());
+/// How geometric primitives are rasterized into cells. Text is always
+/// written one character per cell, on top of the geometry.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderMode {
+    /// One line drawing character per cell.
+    #[default]
+    Text,
+    /// Braille patterns, giving a 2x4 grid of dots per cell.
+    Braille,
+    /// Upper and lower half blocks, giving two pixels per cell.
+    HalfBlock,
+    /// Quadrant blocks, giving a 2x2 grid of pixels per cell.
+    Quadrant,
+    /// Sextant blocks, giving a 2x3 grid of pixels per cell.
+    Sextant,
+}
+
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+impl RenderMode {
+    /// Sub-cell pixels per cell, horizontally and vertically.
+    fn resolution(self) -> (i32, i32) {
+        match self {
+            RenderMode::Text => (1, 1),
+            RenderMode::Braille => (2, 4),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Quadrant => (2, 2),
+            RenderMode::Sextant => (2, 3),
+        }
+    }
+
+    /// The glyph for a cell whose pixels are given row by row, bottom row first.
+    fn compose(self, pixels: &[Option<Style>]) -> Cell {
+        // Cells hold a single color, the one most of the pixels were drawn with.
+        let drawn: Vec<Style> = pixels.iter().flatten().copied().collect();
+        let count = |style: &Style| drawn.iter().filter(|other| *other == style).count();
+        let mut style = drawn.iter().copied().max_by_key(count).unwrap_or_default();
+
+        let ch = match self {
+            RenderMode::Text => POINT_CHAR,
+            RenderMode::Braille => {
+                const DOTS: [[u32; 4]; 2] = [[0x40, 0x04, 0x02, 0x01], [0x80, 0x20, 0x10, 0x08]];
+                let mut bits = 0;
+                for (i, pixel) in pixels.iter().enumerate() {
+                    if pixel.is_some() {
+                        bits |= DOTS[i % 2][i / 2];
+                    }
+                }
+                char::from_u32(0x2800 + bits).unwrap()
+            }
+            _ => {
+                // When no pixel is left empty, the background can show a second color.
+                let full = drawn.len() == pixels.len();
+                let (sx, sy) = self.resolution();
+                let (sx, sy) = (sx as usize, sy as usize);
+                let mut pattern = 0;
+                for (i, pixel) in pixels.iter().enumerate() {
+                    let on = pixel.is_some_and(|pixel| !full || pixel == style);
+                    if on {
+                        let (column, row_from_top) = (i % sx, sy - 1 - i / sx);
+                        pattern |= 1 << (row_from_top * sx + column);
+                    }
+                }
+                if full {
+                    if let Some(other) = drawn.iter().find(|other| **other != style) {
+                        style.bg = other.fg;
+                    }
+                }
+                block_glyph(self, pattern)
             }
+        };
+
+        Cell {
+            ch,
+            cluster: None,
+            strokes: 0,
+            style,
+            opaque: false,
         }
+    }
+}
 
-        if center_coords {
-            self.text(center, &center.to_string());
+/// The block character for a pattern of pixels, numbered row by row from the top left.
+fn block_glyph(mode: RenderMode, pattern: u32) -> char {
+    match (mode, pattern) {
+        (RenderMode::HalfBlock, _) => [' ', '▀', '▄', '█'][pattern as usize],
+        (RenderMode::Quadrant, _) => QUADRANTS[pattern as usize],
+        (_, 0) => ' ',
+        (_, 21) => '▌',
+        (_, 42) => '▐',
+        (_, 63) => '█',
+        // The sextant block skips the four patterns that already had a character.
+        (_, _) => {
+            let skipped = (pattern > 21) as u32 + (pattern > 42) as u32;
+            char::from_u32(0x1FB00 + pattern - 1 - skipped).unwrap()
         }
+    }
+}
 
-        if edge_lengths {
-            let left_center: Vec2 = [center.x - half_width, center.y].into();
-            let bottom_center: Vec2 = [center.x, center.y - half_height].into();
-            let edge_length_x = size.x.round() as i32;
-            let edge_length_y = size.y.round() as i32;
+const DEFAULT_LAYER: &str = "default";
 
-            self.text(left_center, &edge_length_y.to_string());
-            self.text(bottom_center, &edge_length_x.to_string());
-        }
+/// The style each sub-cell pixel was drawn with, by raster position.
+type Pixels = HashMap<(i32, i32), Style>;
 
-        self
+/// A named sheet of cells. Layers are composited in z-order, higher on top,
+/// and spaces let the layers below show through.
+struct Layer {
+    name: String,
+    z: i32,
+    visible: bool,
+    buffer: HashMap<(i32, i32), Cell>,
+    /// Sub-cell pixels, kept apart for each render mode they were drawn in
+    /// since each mode splits cells differently. The mode drawn with most
+    /// recently comes last.
+    pixels: Vec<(RenderMode, Pixels)>,
+    bounds: AABB,
+}
+
+impl Layer {
+    fn new(name: &str, z: i32) -> Self {
+        Layer {
+            name: name.to_string(),
+            z,
+            visible: true,
+            buffer: HashMap::new(),
+            pixels: Vec::new(),
+            bounds: AABB::EMPTY,
+        }
     }
 
-    pub fn draw(&self) {
-        self.canvas.draw();
+    /// The cells of this layer, with its pixels composed into glyphs under
+    /// its text. Where pixels of several modes share a cell, the mode drawn
+    /// with most recently wins.
+    fn cells(&self) -> HashMap<(i32, i32), Cell> {
+        let mut cells = HashMap::new();
+        for (mode, pixels) in &self.pixels {
+            let (sx, sy) = mode.resolution();
+            let mut pixel_cells: HashMap<(i32, i32), Vec<Option<Style>>> = HashMap::new();
+            for (&(x, y), style) in pixels {
+                let cell = (x.div_euclid(sx), y.div_euclid(sy));
+                let cell_pixels = pixel_cells
+                    .entry(cell)
+                    .or_insert_with(|| vec![None; (sx * sy) as usize]);
+                cell_pixels[(y.rem_euclid(sy) * sx + x.rem_euclid(sx)) as usize] = Some(*style);
+            }
+            cells.extend(
+                pixel_cells
+                    .into_iter()
+                    .map(|(position, pixels)| (position, mode.compose(&pixels))),
+            );
+        }
+        cells.extend(
+            self.buffer
+                .iter()
+                .map(|(&position, cell)| (position, cell.clone())),
+        );
+        cells
     }
 }
 
-fn main() {
+/// How much of the shape being drawn ended up outside the clip region.
+#[derive(Default, Debug, Copy, Clone)]
+struct ShapeClip {
+    depth: u32,
+    visible: bool,
+    clipped: u32,
+    clipped_sum: (f32, f32),
+}
+
+pub struct AsciiCanvas {
+    layers: Vec<Layer>,
+    current_layer: usize,
+    fill_char: char,
+    text_layout: TextLayout,
+    line_style: LineStyle,
+    merge_policy: MergePolicy,
+    style: Style,
+    mode: RenderMode,
+    clip: Option<CellRect>,
+    offscreen_indicators: bool,
+    indicators: HashMap<(i32, i32), char>,
+    shape: ShapeClip,
+    y_axis: YAxis,
+    axes: bool,
+    padding: Spacing,
+    include_origin: bool,
+    aspect_ratio: f32,
+}
+
+impl AsciiCanvas {
+    pub fn new() -> Self {
+        AsciiCanvas {
+            layers: vec![Layer::new(DEFAULT_LAYER, 0)],
+            current_layer: 0,
+            fill_char: DEFAULT_FILL_CHAR,
+            text_layout: TextLayout::default(),
+            line_style: LineStyle::default(),
+            merge_policy: MergePolicy::default(),
+            style: Style::default(),
+            mode: RenderMode::default(),
+            clip: None,
+            offscreen_indicators: false,
+            indicators: HashMap::new(),
+            shape: ShapeClip::default(),
+            y_axis: YAxis::default(),
+            axes: false,
+            padding: Spacing::default(),
+            include_origin: false,
+            aspect_ratio: CELL_ASPECT,
+        }
+    }
+
+    /// Sets how many times taller than wide a cell is displayed, 2 by default.
+    /// Slanted lines and curves drawn afterwards pick their glyphs to match.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets which way +y points for the following primitives and clip regions.
+    pub fn set_y_axis(&mut self, y_axis: YAxis) -> &mut Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    /// Draws the x and y axes beneath everything else, with an arrow at the
+    /// positive end of each, so the output shows which way +y points.
+    pub fn set_axes(&mut self, enabled: bool) -> &mut Self {
+        self.axes = enabled;
+        self
+    }
+
+    /// Adds empty cells around what was drawn to every output. Ignored while
+    /// a clip region is set, which already fixes the output size.
+    pub fn set_padding(&mut self, padding: Spacing) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Extends the output to the origin even when nothing was drawn there.
+    pub fn set_include_origin(&mut self, enabled: bool) -> &mut Self {
+        self.include_origin = enabled;
+        self
+    }
+
+    /// Maps `point` to the internal cell coordinates, where +y is always up.
+    fn oriented(&self, point: Vec2) -> Vec2 {
+        match self.y_axis {
+            YAxis::Up => point,
+            YAxis::Down => [point.x, -point.y].into(),
+        }
+    }
+
+    /// Restricts drawing and the rendered output to the cells inside `region`,
+    /// or lifts the restriction with `None`.
+    pub fn set_clip(&mut self, region: Option<AABB>) -> &mut Self {
+        let (y_min, y_max) = match (self.y_axis, region) {
+            (YAxis::Down, Some(region)) => (-region.y_max, -region.y_min),
+            (_, Some(region)) => (region.y_min, region.y_max),
+            (_, None) => (0.0, 0.0),
+        };
+        self.clip = region.map(|region| CellRect {
+            left: region.x_min.round() as i32,
+            right: region.x_max.round() as i32,
+            bottom: y_min.round() as i32,
+            top: y_max.round() as i32,
+        });
+        self
+    }
+
+    /// Marks shapes drawn entirely outside the clip region with an arrow on
+    /// the edge of the output that faces them.
+    pub fn set_offscreen_indicators(&mut self, enabled: bool) -> &mut Self {
+        self.offscreen_indicators = enabled;
+        self
+    }
+
+    fn begin_shape(&mut self) {
+        if self.shape.depth == 0 {
+            self.shape = ShapeClip::default();
+        }
+        self.shape.depth += 1;
+    }
+
+    fn end_shape(&mut self) -> &mut Self {
+        self.shape.depth -= 1;
+        let shape = self.shape;
+        if shape.depth > 0 || shape.visible || shape.clipped == 0 || !self.offscreen_indicators {
+            return self;
+        }
+        let Some(clip) = self.clip else {
+            return self;
+        };
+
+        let x = shape.clipped_sum.0 / shape.clipped as f32;
+        let y = shape.clipped_sum.1 / shape.clipped as f32;
+        let overshoot_x = (x - clip.right as f32).max(clip.left as f32 - x).max(0.0);
+        let overshoot_y = (y - clip.top as f32).max(clip.bottom as f32 - y).max(0.0);
+        let arrow = if overshoot_x >= overshoot_y * self.aspect_ratio {
+            if x > clip.right as f32 {
+                '>'
+            } else {
+                '<'
+            }
+        } else if y > clip.top as f32 {
+            '^'
+        } else {
+            'v'
+        };
+
+        let edge_x = (x.round() as i32).clamp(clip.left, clip.right);
+        let edge_y = (y.round() as i32).clamp(clip.bottom, clip.top);
+        self.indicators.insert((edge_x, edge_y), arrow);
+        self
+    }
+
+    /// Whether cell (x, y) may be drawn, keeping track of what the clip region hides.
+    fn unclipped(&mut self, x: i32, y: i32) -> bool {
+        let inside = self.clip.is_none_or(|clip| clip.contains(x, y));
+        if inside {
+            self.shape.visible = true;
+        } else {
+            self.shape.clipped += 1;
+            self.shape.clipped_sum.0 += x as f32;
+            self.shape.clipped_sum.1 += y as f32;
+        }
+        inside
+    }
+
+    /// Sends the following primitives to the layer `name`, creating it if
+    /// needed, and moves that layer to z-order `z`. Everything starts on the
+    /// `"default"` layer at z-order 0.
+    pub fn layer(&mut self, name: &str, z: i32) -> &mut Self {
+        match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => {
+                self.layers[index].z = z;
+                self.current_layer = index;
+            }
+            None => {
+                self.layers.push(Layer::new(name, z));
+                self.current_layer = self.layers.len() - 1;
+            }
+        }
+        self
+    }
+
+    /// Shows or hides the layer `name` when rendering. Unknown layers are ignored.
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> &mut Self {
+        if let Some(layer) = self.layers.iter_mut().find(|layer| layer.name == name) {
+            layer.visible = visible;
+        }
+        self
+    }
+
+    fn layer_visible(&self, name: &str) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.name == name && layer.visible)
+    }
+
+    fn layer_z(&self, name: &str) -> Option<i32> {
+        self.layers
+            .iter()
+            .find(|layer| layer.name == name)
+            .map(|layer| layer.z)
+    }
+
+    fn current_layer(&mut self) -> &mut Layer {
+        &mut self.layers[self.current_layer]
+    }
+
+    /// Sets how the following primitives are rasterized. Geometry already
+    /// drawn keeps the mode it was drawn in.
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the style used by every following primitive.
+    pub fn set_style(&mut self, style: Style) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    pub fn set_line_style(&mut self, line_style: LineStyle) -> &mut Self {
+        self.line_style = line_style;
+        self
+    }
+
+    pub fn set_merge_policy(&mut self, merge_policy: MergePolicy) -> &mut Self {
+        self.merge_policy = merge_policy;
+        self
+    }
+
+    pub fn set_fill_char(&mut self, fill_char: char) -> &mut Self {
+        self.fill_char = fill_char;
+        self
+    }
+
+    /// Sets how the following texts are anchored and how their lines are spaced.
+    pub fn set_text_layout(&mut self, layout: TextLayout) -> &mut Self {
+        self.text_layout = layout;
+        self
+    }
+
+    fn put(&mut self, x: i32, y: i32, ch: char) {
+        let style = self.style;
+        self.put_cell(
+            x,
+            y,
+            Cell {
+                ch,
+                cluster: None,
+                strokes: 0,
+                style,
+                opaque: false,
+            },
+        );
+    }
+
+    fn stroke(&mut self, x: i32, y: i32, strokes: u8) {
+        let strokes = match self.layers[self.current_layer].buffer.get(&(x, y)) {
+            Some(previous) if self.merge_policy == MergePolicy::Merge => {
+                let same_kind = |mask: u8| previous.strokes & mask != 0 && strokes & mask != 0;
+                if same_kind(STROKE_ORTHOGONAL) || same_kind(STROKE_DIAGONAL) {
+                    previous.strokes | strokes
+                } else {
+                    strokes
+                }
+            }
+            _ => strokes,
+        };
+        let ch = self.line_style.glyph(strokes);
+        let style = self.style;
+        self.put_cell(
+            x,
+            y,
+            Cell {
+                ch,
+                cluster: None,
+                strokes,
+                style,
+                opaque: false,
+            },
+        );
+    }
+
+    fn put_cell(&mut self, x: i32, y: i32, cell: Cell) {
+        if !self.unclipped(x, y) {
+            return;
+        }
+        let layer = self.current_layer();
+        layer.buffer.insert((x, y), cell);
+        layer.bounds.include_point([x as f32, y as f32].into());
+    }
+
+    /// Maps cell coordinates onto the raster of the current render mode.
+    fn to_raster(&self, point: Vec2) -> Vec2 {
+        let point = self.oriented(point);
+        let (sx, sy) = self.mode.resolution();
+        [
+            (point.x + 0.5) * sx as f32 - 0.5,
+            (point.y + 0.5) * sy as f32 - 0.5,
+        ]
+        .into()
+    }
+
+    /// Draws part of an outline at raster position (x, y).
+    fn mark(&mut self, x: i32, y: i32, strokes: u8) {
+        match self.mode {
+            RenderMode::Text => self.stroke(x, y, strokes),
+            _ => self.set_pixel(x, y),
+        }
+    }
+
+    /// Draws part of a filled area at raster position (x, y).
+    fn fill(&mut self, x: i32, y: i32) {
+        match self.mode {
+            RenderMode::Text => self.put(x, y, self.fill_char),
+            _ => self.set_pixel(x, y),
+        }
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32) {
+        let (sx, sy) = self.mode.resolution();
+        if !self.unclipped(x.div_euclid(sx), y.div_euclid(sy)) {
+            return;
+        }
+        let (style, mode) = (self.style, self.mode);
+        let layer = self.current_layer();
+        if layer.pixels.last().is_none_or(|(last, _)| *last != mode) {
+            let bucket = match layer.pixels.iter().position(|(used, _)| *used == mode) {
+                Some(index) => layer.pixels.remove(index),
+                None => (mode, HashMap::new()),
+            };
+            layer.pixels.push(bucket);
+        }
+        let (_, pixels) = layer.pixels.last_mut().expect("a bucket was just pushed");
+        pixels.insert((x, y), style);
+        layer
+            .bounds
+            .include_point([x.div_euclid(sx) as f32, y.div_euclid(sy) as f32].into());
+    }
+
+    pub fn rect(&mut self, center: Vec2, size: Vec2) -> &mut Self {
+        self.begin_shape();
+        let (sx, sy) = self.mode.resolution();
+        let raster_size = [size.x * sx as f32, size.y * sy as f32].into();
+        let CellRect {
+            left,
+            right,
+            bottom,
+            top,
+        } = rect_frame(self.to_raster(center), raster_size);
+
+        for px in left..=right {
+            let mut strokes = 0;
+            if px > left {
+                strokes |= STROKE_LEFT;
+            }
+            if px < right {
+                strokes |= STROKE_RIGHT;
+            }
+            let (up, down) = if (px == left || px == right) && top > bottom {
+                (STROKE_UP, STROKE_DOWN)
+            } else {
+                (0, 0)
+            };
+            self.mark(px, bottom, strokes | up);
+            self.mark(px, top, strokes | down);
+        }
+
+        for py in bottom + 1..top {
+            self.mark(left, py, STROKE_VERTICAL);
+            self.mark(right, py, STROKE_VERTICAL);
+        }
+
+        self.end_shape()
+    }
+
+    pub fn text(&mut self, position: Vec2, text: &str) -> &mut Self {
+        self.begin_shape();
+        let clusters = layout_text(self.oriented(position), text, &self.text_layout);
+
+        // The spaces between the words of a line hide what is below them.
+        let mut words: HashMap<i32, (i32, i32)> = HashMap::new();
+        for &((x, y), cluster) in &clusters {
+            if cluster != " " {
+                let span = words.entry(y).or_insert((x, x));
+                *span = (span.0.min(x), span.1.max(x));
+            }
+        }
+
+        for ((x, y), cluster) in clusters {
+            let mut chars = cluster.chars();
+            let ch = chars.next().unwrap_or(' ');
+            let cell = Cell {
+                ch,
+                cluster: chars.next().map(|_| cluster.into()),
+                strokes: 0,
+                style: self.style,
+                opaque: words
+                    .get(&y)
+                    .is_some_and(|&(first, last)| (first..=last).contains(&x)),
+            };
+            if unicode::cluster_width(cluster) == 2 {
+                let continuation = Cell {
+                    ch: WIDE_CONTINUATION,
+                    cluster: None,
+                    ..cell.clone()
+                };
+                self.put_cell(x + 1, y, continuation);
+            }
+            self.put_cell(x, y, cell);
+        }
+
+        self.end_shape()
+    }
+
+    pub fn rect_styled(&mut self, center: Vec2, size: Vec2, style: Style) -> &mut Self {
+        self.with_style(style, |canvas| canvas.rect(center, size))
+    }
+
+    pub fn text_styled(&mut self, position: Vec2, text: &str, style: Style) -> &mut Self {
+        self.with_style(style, |canvas| canvas.text(position, text))
+    }
+
+    pub fn text_with_layout(
+        &mut self,
+        position: Vec2,
+        text: &str,
+        layout: TextLayout,
+    ) -> &mut Self {
+        let previous = std::mem::replace(&mut self.text_layout, layout);
+        self.text(position, text);
+        self.text_layout = previous;
+        self
+    }
+
+    /// Draws `rect(center, size)` with `title` written into its top border,
+    /// like `+-- Title --+`, and `footer` into its bottom border. Texts too
+    /// long for the border are cut short with an ellipsis.
+    pub fn panel(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        title: &str,
+        footer: Option<&str>,
+        alignment: HorizontalAnchor,
+    ) -> &mut Self {
+        self.begin_shape();
+        self.rect(center, size);
+        self.panel_titles(center, size, title, footer, alignment);
+        self.end_shape()
+    }
+
+    fn panel_titles(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        title: &str,
+        footer: Option<&str>,
+        alignment: HorizontalAnchor,
+    ) {
+        let frame = rect_frame(self.oriented(center), size);
+        // Keep a corner and two strokes on either side, plus a space around the text.
+        let room = (frame.right - frame.left - 7).max(0) as usize;
+        let borders = [(frame.top, Some(title)), (frame.bottom, footer)];
+        for (y, text) in borders {
+            let Some(text) = text.filter(|text| !text.is_empty()) else {
+                continue;
+            };
+            let Some(line) = wrap_text(text, room, 1)
+                .pop()
+                .filter(|line| !line.is_empty())
+            else {
+                continue;
+            };
+            let label = format!(" {line} ");
+            let slack = (frame.right - frame.left - 5) - unicode::text_width(&label) as i32;
+            let x = frame.left
+                + 3
+                + match alignment {
+                    HorizontalAnchor::Left => 0,
+                    HorizontalAnchor::Center => slack / 2,
+                    HorizontalAnchor::Right => slack,
+                };
+            let position = self.oriented([x as f32, y as f32].into());
+            let layout = TextLayout::new(HorizontalAnchor::Left, VerticalAnchor::Top);
+            self.text_with_layout(position, &label, layout);
+        }
+    }
+
+    /// Writes `text` inside the outline `rect(center, size)` would draw,
+    /// word-wrapped to its width and truncated with an ellipsis when it has
+    /// too many lines. `layout` aligns the lines and the whole paragraph.
+    pub fn text_in_rect(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        text: &str,
+        layout: TextLayout,
+    ) -> &mut Self {
+        let frame = rect_frame(self.oriented(center), size);
+        let width = (frame.right - frame.left - 1).max(0) as usize;
+        let height = (frame.top - frame.bottom - 1).max(0) as usize;
+        let step = 1 + layout.line_spacing as usize;
+        let lines = wrap_text(text, width, height.div_ceil(step));
+        if lines.is_empty() {
+            return self;
+        }
+
+        let block = ((lines.len() - 1) * step + 1) as i32;
+        let top = frame.top
+            - 1
+            - match layout.vertical {
+                VerticalAnchor::Top => 0,
+                VerticalAnchor::Middle => (height as i32 - block) / 2,
+                VerticalAnchor::Bottom => height as i32 - block,
+            };
+
+        self.begin_shape();
+        let line_layout = TextLayout::new(HorizontalAnchor::Left, VerticalAnchor::Top);
+        for (i, line) in lines.iter().enumerate() {
+            let slack = width as i32 - unicode::text_width(line) as i32;
+            let x = frame.left
+                + 1
+                + match layout.horizontal {
+                    HorizontalAnchor::Left => 0,
+                    HorizontalAnchor::Center => slack / 2,
+                    HorizontalAnchor::Right => slack,
+                };
+            let y = top - (i * step) as i32;
+            // Flipping y is its own inverse, this takes the cell back to the
+            // caller's orientation.
+            let position = self.oriented([x as f32, y as f32].into());
+            self.text_with_layout(position, line, line_layout);
+        }
+        self.end_shape()
+    }
+
+    fn with_style(&mut self, style: Style, draw: impl FnOnce(&mut Self) -> &mut Self) -> &mut Self {
+        let previous = std::mem::replace(&mut self.style, style);
+        draw(self);
+        self.style = previous;
+        self
+    }
+
+    pub fn point(&mut self, position: Vec2) -> &mut Self {
+        self.begin_shape();
+        let raster = self.to_raster(position);
+        let (x, y) = (raster.x.round() as i32, raster.y.round() as i32);
+        match self.mode {
+            RenderMode::Text => self.put(x, y, POINT_CHAR),
+            _ => self.set_pixel(x, y),
+        }
+        self.end_shape()
+    }
+
+    pub fn line(&mut self, from: Vec2, to: Vec2) -> &mut Self {
+        self.begin_shape();
+        let (from, to) = (self.to_raster(from), self.to_raster(to));
+        let strokes = slope_strokes(to.x - from.x, to.y - from.y, self.aspect_ratio);
+        let (mut x, mut y) = (from.x.round() as i32, from.y.round() as i32);
+        let (x_end, y_end) = (to.x.round() as i32, to.y.round() as i32);
+
+        let dx = (x_end - x).abs();
+        let dy = -(y_end - y).abs();
+        let step_x = if x < x_end { 1 } else { -1 };
+        let step_y = if y < y_end { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        // The ends of a stroke only reach inwards, so that a line ending on
+        // another one forms a tee, and two slanted lines meeting at a corner
+        // form an apex, instead of a cross.
+        let (backwards, forwards) = match strokes {
+            STROKE_HORIZONTAL if step_x > 0 => (STROKE_LEFT, STROKE_RIGHT),
+            STROKE_HORIZONTAL => (STROKE_RIGHT, STROKE_LEFT),
+            STROKE_VERTICAL if step_y > 0 => (STROKE_DOWN, STROKE_UP),
+            STROKE_VERTICAL => (STROKE_UP, STROKE_DOWN),
+            STROKE_RISING if step_x > 0 => (STROKE_DOWN_LEFT, STROKE_UP_RIGHT),
+            STROKE_RISING => (STROKE_UP_RIGHT, STROKE_DOWN_LEFT),
+            STROKE_FALLING if step_x > 0 => (STROKE_UP_LEFT, STROKE_DOWN_RIGHT),
+            STROKE_FALLING => (STROKE_DOWN_RIGHT, STROKE_UP_LEFT),
+            _ => (0, 0),
+        };
+        let (x_start, y_start) = (x, y);
+
+        loop {
+            let mut cell_strokes = strokes;
+            if (x, y) != (x_end, y_end) && (x, y) == (x_start, y_start) {
+                cell_strokes &= !backwards;
+            }
+            if (x, y) != (x_start, y_start) && (x, y) == (x_end, y_end) {
+                cell_strokes &= !forwards;
+            }
+            self.mark(x, y, cell_strokes);
+            if x == x_end && y == y_end {
+                break;
+            }
+            let err2 = 2 * err;
+            if err2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+            if err2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+
+        self.end_shape()
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32) -> &mut Self {
+        self.ellipse(center, [radius, radius].into())
+    }
+
+    pub fn filled_circle(&mut self, center: Vec2, radius: f32) -> &mut Self {
+        self.filled_ellipse(center, [radius, radius].into())
+    }
+
+    pub fn ellipse(&mut self, center: Vec2, radii: Vec2) -> &mut Self {
+        self.rasterize_ellipse(center, radii, false)
+    }
+
+    pub fn filled_ellipse(&mut self, center: Vec2, radii: Vec2) -> &mut Self {
+        self.rasterize_ellipse(center, radii, true)
+    }
+
+    fn rasterize_ellipse(&mut self, center: Vec2, radii: Vec2, filled: bool) -> &mut Self {
+        self.begin_shape();
+        let (sx, sy) = self.mode.resolution();
+        let center = self.to_raster(center);
+        let radii: Vec2 = [radii.x * sx as f32, radii.y * sy as f32].into();
+
+        // Degenerate radii still cover the center cell, and nothing more.
+        let rx = radii.x.abs().max(0.49);
+        let ry = radii.y.abs().max(0.49);
+        let half_chord = |offset: f32, radius: f32, other_radius: f32| {
+            other_radius * (1.0 - (offset / radius).powi(2)).max(0.0).sqrt()
+        };
+
+        let columns = (center.x - rx).ceil() as i32..=(center.x + rx).floor() as i32;
+        let rows = (center.y - ry).ceil() as i32..=(center.y + ry).floor() as i32;
+
+        if filled {
+            for x in columns.clone() {
+                let h = half_chord(x as f32 - center.x, rx, ry);
+                for y in (center.y - h).round() as i32..=(center.y + h).round() as i32 {
+                    self.fill(x, y);
+                }
+            }
+        }
+
+        // Sampling both per column and per row keeps the outline connected
+        // on the steep and on the flat parts of the curve.
+        let mut outline = Vec::new();
+        for x in columns {
+            let h = half_chord(x as f32 - center.x, rx, ry);
+            outline.push((x, (center.y - h).round() as i32));
+            outline.push((x, (center.y + h).round() as i32));
+        }
+        for y in rows {
+            let w = half_chord(y as f32 - center.y, ry, rx);
+            outline.push(((center.x - w).round() as i32, y));
+            outline.push(((center.x + w).round() as i32, y));
+        }
+
+        for (x, y) in outline {
+            // The tangent is perpendicular to the gradient of the implicit equation.
+            let tangent_x = -(y as f32 - center.y) / (ry * ry);
+            let tangent_y = (x as f32 - center.x) / (rx * rx);
+            let strokes = slope_strokes(tangent_x, tangent_y, self.aspect_ratio);
+            self.mark(x, y, strokes);
+        }
+
+        self.end_shape()
+    }
+
+    pub fn polyline(&mut self, points: &[Vec2]) -> &mut Self {
+        self.begin_shape();
+        for segment in points.windows(2) {
+            self.line(segment[0], segment[1]);
+        }
+        if let [point] = points {
+            self.line(*point, *point);
+        }
+        self.end_shape()
+    }
+
+    pub fn polygon(&mut self, points: &[Vec2]) -> &mut Self {
+        self.begin_shape();
+        self.polyline(points);
+        if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+            self.line(last, first);
+        }
+        self.end_shape()
+    }
+
+    pub fn filled_polygon(&mut self, points: &[Vec2]) -> &mut Self {
+        self.begin_shape();
+        let raster: Vec<Vec2> = points.iter().map(|&point| self.to_raster(point)).collect();
+        let (y_min, y_max) = raster.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+            (lo.min(p.y), hi.max(p.y))
+        });
+
+        // Even-odd scanline fill, sampling each row through the cell centers.
+        let mut crossings = Vec::new();
+        for y in y_min.ceil() as i32..=y_max.floor() as i32 {
+            let scan_y = y as f32;
+            crossings.clear();
+            for (i, a) in raster.iter().enumerate() {
+                let b = raster[(i + 1) % raster.len()];
+                if (a.y <= scan_y) != (b.y <= scan_y) {
+                    crossings.push(a.x + (scan_y - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                for x in span[0].ceil() as i32..=span[1].floor() as i32 {
+                    self.fill(x, y);
+                }
+            }
+        }
+
+        self.polygon(points);
+        self.end_shape()
+    }
+
+    /// The cells covered by the rendered output.
+    fn frame(&self) -> CellRect {
+        if let Some(clip) = self.clip {
+            return clip;
+        }
+
+        // Only the cells actually written count, plus the origin if asked for.
+        let mut bounds = AABB::EMPTY;
+        if self.include_origin || self.axes {
+            bounds.include_point(Vec2::ZERO);
+        }
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            bounds = bounds.union(&layer.bounds);
+        }
+
+        if bounds.is_empty() {
+            return CellRect {
+                left: 0,
+                right: -1,
+                bottom: 0,
+                top: -1,
+            };
+        }
+
+        CellRect {
+            left: bounds.x_min.floor() as i32 - self.padding.left as i32,
+            right: bounds.x_max.ceil() as i32 + self.padding.right as i32,
+            bottom: bounds.y_min.floor() as i32 - self.padding.bottom as i32,
+            top: bounds.y_max.ceil() as i32 + self.padding.top as i32,
+        }
+    }
+
+    fn rows(&self) -> Vec<Vec<Cell>> {
+        let frame = self.frame();
+        let mut canvas = vec![vec![EMPTY_CELL; frame.width()]; frame.height()];
+        if self.axes {
+            self.draw_axes(&mut canvas, frame);
+        }
+
+        let mut layers: Vec<&Layer> = self.layers.iter().filter(|layer| layer.visible).collect();
+        layers.sort_by_key(|layer| layer.z);
+
+        for layer in layers {
+            for ((x, y), cell) in layer.cells() {
+                if cell.ch == ' ' && cell.style.bg.is_none() && !cell.opaque
+                    || !frame.contains(x, y)
+                {
+                    continue;
+                }
+                let canvas_x = (x - frame.left) as usize;
+                let canvas_y = (y - frame.bottom) as usize;
+                canvas[canvas_y][canvas_x] = cell;
+            }
+        }
+
+        if self.offscreen_indicators {
+            for (&(x, y), &arrow) in &self.indicators {
+                if !frame.contains(x, y) {
+                    continue;
+                }
+                let canvas_x = (x - frame.left) as usize;
+                let canvas_y = (y - frame.bottom) as usize;
+                canvas[canvas_y][canvas_x] = Cell {
+                    ch: arrow,
+                    ..EMPTY_CELL
+                };
+            }
+        }
+
+        // A wide character whose right half was drawn over, or the other way
+        // around, is blanked so that the rest of the row stays aligned.
+        let is_wide = |cell: &Cell| {
+            cell.ch != WIDE_CONTINUATION && unicode::char_width(cell.ch) == 2
+                || cell
+                    .cluster
+                    .as_deref()
+                    .is_some_and(|c| unicode::cluster_width(c) == 2)
+        };
+        for row in &mut canvas {
+            for x in 0..row.len() {
+                let wide = is_wide(&row[x]);
+                let continued = row
+                    .get(x + 1)
+                    .is_some_and(|next| next.ch == WIDE_CONTINUATION);
+                let orphan = row[x].ch == WIDE_CONTINUATION && (x == 0 || !is_wide(&row[x - 1]));
+                if wide && !continued || orphan {
+                    row[x].ch = ' ';
+                    row[x].cluster = None;
+                }
+            }
+        }
+
+        // Rows are stored bottom-up, +y points up on screen.
+        canvas.reverse();
+        canvas
+    }
+
+    fn draw_axes(&self, canvas: &mut [Vec<Cell>], frame: CellRect) {
+        let axis_cell = |ch: char, strokes: u8| Cell {
+            ch,
+            strokes,
+            ..EMPTY_CELL
+        };
+        let glyph = |strokes: u8| axis_cell(self.line_style.glyph(strokes), strokes);
+
+        if frame.contains(frame.left, 0) {
+            let row = &mut canvas[(0 - frame.bottom) as usize];
+            row.fill(glyph(STROKE_HORIZONTAL));
+            row[frame.width() - 1] = axis_cell('>', 0);
+        }
+        if frame.contains(0, frame.bottom) {
+            let column = (0 - frame.left) as usize;
+            for row in canvas.iter_mut() {
+                row[column] = glyph(STROKE_VERTICAL);
+            }
+            let (row, arrow) = match self.y_axis {
+                YAxis::Up => (frame.height() - 1, '^'),
+                YAxis::Down => (0, 'v'),
+            };
+            canvas[row][column] = axis_cell(arrow, 0);
+        }
+        if frame.contains(0, 0) {
+            canvas[(0 - frame.bottom) as usize][(0 - frame.left) as usize] =
+                glyph(STROKE_ORTHOGONAL);
+        }
+    }
+
+    pub fn render_with(&self, options: &RenderOptions) -> String {
+        let mut output = String::new();
+
+        if options.disable_line_wrap {
+            output.push_str("\x1B[?7l");
+        }
+
+        let margin = options.margin;
+        let rows = self.rows();
+        let blank_line =
+            " ".repeat(rows.first().map_or(0, Vec::len) + (margin.left + margin.right) as usize);
+        for _ in 0..margin.top {
+            output.push_str(&blank_line);
+            output.push('\n');
+        }
+
+        for row in rows {
+            output.push_str(&" ".repeat(margin.left as usize));
+            let mut current = Style::default();
+            for cell in row {
+                if options.color && cell.style != current {
+                    current = cell.style;
+                    output.push_str(&current.sgr());
+                }
+                cell.push_to(&mut output);
+            }
+            if current != Style::default() {
+                output.push_str("\x1B[0m");
+            }
+            output.push_str(&" ".repeat(margin.right as usize));
+            output.push('\n');
+        }
+
+        for _ in 0..margin.bottom {
+            output.push_str(&blank_line);
+            output.push('\n');
+        }
+
+        if options.disable_line_wrap {
+            output.push_str("\x1B[?7h");
+        }
+
+        output
+    }
+
+    pub fn render_to_string(&self) -> String {
+        self.render_with(&RenderOptions::default())
+    }
+
+    pub fn write_with(&self, out: &mut impl io::Write, options: &RenderOptions) -> io::Result<()> {
+        out.write_all(self.render_with(options).as_bytes())
+    }
+
+    pub fn write_to(&self, out: &mut impl io::Write) -> io::Result<()> {
+        self.write_with(out, &RenderOptions::default())
+    }
+
+    pub fn draw(&self) {
+        print!("{}", self.render_with(&RenderOptions::terminal()));
+    }
+
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        svg::render(self, options, &[])
+    }
+
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        html::render(self, options, &HashMap::new())
+    }
+
+    pub fn to_png(&self, options: &PngOptions) -> Vec<u8> {
+        png::render(self, options)
+    }
+
+    pub fn write_png(&self, out: &mut impl io::Write, options: &PngOptions) -> io::Result<()> {
+        png::write(self, out, options)
+    }
+}
+
+impl fmt::Display for AsciiCanvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render_to_string())
+    }
+}
+
+impl Default for AsciiCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A drawing call recorded by [`AsciiDrawer`], in world coordinates.
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    Rect {
+        center: Vec2,
+        size: Vec2,
+    },
+    Text {
+        position: Vec2,
+        text: String,
+    },
+    /// The coordinates of `point`, written at `point`.
+    PointLabel {
+        point: Vec2,
+    },
+    Point {
+        position: Vec2,
+    },
+    Line {
+        from: Vec2,
+        to: Vec2,
+    },
+    Ellipse {
+        center: Vec2,
+        radii: Vec2,
+        filled: bool,
+    },
+    Polyline {
+        points: Vec<Vec2>,
+    },
+    Polygon {
+        points: Vec<Vec2>,
+        filled: bool,
+    },
+    SetStyle(Style),
+    SetFillChar(char),
+    SetTextLayout(TextLayout),
+    /// The title and footer of a panel, written over the border of the rect
+    /// recorded before it.
+    PanelTitles {
+        center: Vec2,
+        size: Vec2,
+        title: String,
+        footer: Option<String>,
+        alignment: HorizontalAnchor,
+    },
+    /// Text wrapped to the inside of a rect, see [`AsciiCanvas::text_in_rect`].
+    TextInRect {
+        center: Vec2,
+        size: Vec2,
+        text: String,
+        layout: TextLayout,
+    },
+    SetLineStyle(LineStyle),
+    SetMergePolicy(MergePolicy),
+    SetRenderMode(RenderMode),
+    Layer {
+        name: String,
+        z: i32,
+    },
+    SetLayerVisible {
+        name: String,
+        visible: bool,
+    },
+}
+
+impl DrawCommand {
+    /// The world region covered by what the command draws.
+    fn extent(&self) -> AABB {
+        match self {
+            DrawCommand::Rect { center, size }
+            | DrawCommand::TextInRect { center, size, .. }
+            | DrawCommand::PanelTitles { center, size, .. } => {
+                AABB::from_center_size(*center, *size)
+            }
+            DrawCommand::Ellipse { center, radii, .. } => {
+                AABB::from_center_size(*center, *radii * 2.0)
+            }
+            DrawCommand::Text { position, .. }
+            | DrawCommand::PointLabel { point: position }
+            | DrawCommand::Point { position } => AABB::from_points([*position]),
+            DrawCommand::Line { from, to } => AABB::from_points([*from, *to]),
+            DrawCommand::Polyline { points } | DrawCommand::Polygon { points, .. } => {
+                AABB::from_points(points.iter().copied())
+            }
+            _ => AABB::EMPTY,
+        }
+    }
+
+    /// How many columns and rows the text written by the command reaches
+    /// past its position on either side, whatever the scale, when lines are
+    /// `line_spacing` rows apart.
+    fn overhang(&self, line_spacing: u32) -> (u32, u32) {
+        let text = match self {
+            DrawCommand::Text { text, .. } => text,
+            DrawCommand::PointLabel { point } => &point.to_string(),
+            _ => return (0, 0),
+        };
+        let lines = text.split('\n');
+        let height = (lines.clone().count() - 1) * (1 + line_spacing as usize);
+        let width = lines.map(unicode::text_width).max().unwrap_or(0);
+        (width.div_ceil(2) as u32, height.div_ceil(2) as u32)
+    }
+
+    /// Whether the command puts anything on the canvas, rather than only
+    /// changing how later commands draw.
+    fn draws(&self) -> bool {
+        !matches!(
+            self,
+            DrawCommand::SetStyle(_)
+                | DrawCommand::SetFillChar(_)
+                | DrawCommand::SetTextLayout(_)
+                | DrawCommand::SetLineStyle(_)
+                | DrawCommand::SetMergePolicy(_)
+                | DrawCommand::SetRenderMode(_)
+                | DrawCommand::Layer { .. }
+                | DrawCommand::SetLayerVisible { .. }
+        )
+    }
+
+    /// Draws the command on `canvas`, mapping world coordinates to cells by `scale`.
+    fn execute(&self, canvas: &mut AsciiCanvas, scale: Vec2) {
+        match self {
+            DrawCommand::Rect { center, size } => canvas.rect(*center * scale, *size * scale),
+            DrawCommand::Text { position, text } => canvas.text(*position * scale, text),
+            DrawCommand::TextInRect {
+                center,
+                size,
+                text,
+                layout,
+            } => canvas.text_in_rect(*center * scale, *size * scale, text, *layout),
+            DrawCommand::PanelTitles {
+                center,
+                size,
+                title,
+                footer,
+                alignment,
+            } => {
+                canvas.panel_titles(
+                    *center * scale,
+                    *size * scale,
+                    title,
+                    footer.as_deref(),
+                    *alignment,
+                );
+                canvas
+            }
+            DrawCommand::PointLabel { point } => canvas.text(*point * scale, &point.to_string()),
+            DrawCommand::Point { position } => canvas.point(*position * scale),
+            DrawCommand::Line { from, to } => canvas.line(*from * scale, *to * scale),
+            DrawCommand::Ellipse {
+                center,
+                radii,
+                filled: false,
+            } => canvas.ellipse(*center * scale, *radii * scale),
+            DrawCommand::Ellipse {
+                center,
+                radii,
+                filled: true,
+            } => canvas.filled_ellipse(*center * scale, *radii * scale),
+            DrawCommand::Polyline { points } => canvas.polyline(&scaled(points, scale)),
+            DrawCommand::Polygon {
+                points,
+                filled: false,
+            } => canvas.polygon(&scaled(points, scale)),
+            DrawCommand::Polygon {
+                points,
+                filled: true,
+            } => canvas.filled_polygon(&scaled(points, scale)),
+            DrawCommand::SetStyle(style) => canvas.set_style(*style),
+            DrawCommand::SetFillChar(fill_char) => canvas.set_fill_char(*fill_char),
+            DrawCommand::SetTextLayout(layout) => canvas.set_text_layout(*layout),
+            DrawCommand::SetLineStyle(line_style) => canvas.set_line_style(*line_style),
+            DrawCommand::SetMergePolicy(merge_policy) => canvas.set_merge_policy(*merge_policy),
+            DrawCommand::SetRenderMode(mode) => canvas.set_render_mode(*mode),
+            DrawCommand::Layer { name, z } => canvas.layer(name, *z),
+            DrawCommand::SetLayerVisible { name, visible } => {
+                canvas.set_layer_visible(name, *visible)
+            }
+        };
+    }
+}
+
+/// The layer `rect_with_labels` writes its labels to, and its z-order unless
+/// the layer was created beforehand.
+pub const LABELS_LAYER: &str = "labels";
+const LABELS_Z: i32 = 100;
+
+/// A window onto the world, rendered at a fixed size whatever is drawn.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    /// The region of the world to show.
+    pub window: AABB,
+    /// Width of the output in cells.
+    pub columns: u32,
+    /// Height of the output in cells.
+    pub rows: u32,
+    /// Mark shapes drawn entirely outside the window with an arrow on the
+    /// edge that faces them.
+    pub indicators: bool,
+}
+
+pub struct AsciiDrawer {
+    canvas: AsciiCanvas,
+    scale: Vec2,
+    history: Vec<DrawCommand>,
+    viewport: Option<Viewport>,
+    transform: Transform,
+    transform_stack: Vec<Transform>,
+    y_axis: YAxis,
+    axes: bool,
+    padding: Spacing,
+    include_origin: bool,
+    fit: Option<(u32, u32)>,
+    extent: AABB,
+    overhang: (u32, u32),
+    aspect_ratio: Option<f32>,
+    /// While fitting, `canvas` only keeps track of the drawing state and the
+    /// drawing itself is replayed here, once, when the output is asked for.
+    fitted: OnceCell<AsciiCanvas>,
+}
+
+/// The size of the terminal in columns and rows, as reported by the
+/// terminal itself. When no terminal is attached, falls back to `COLUMNS`
+/// and `LINES` from the environment, and then to 80x24.
+pub fn terminal_size() -> (u32, u32) {
+    if let Some(size) = terminal::window_size() {
+        return size;
+    }
+    let read = |name: &str, default: u32| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|&value| value > 0)
+            .unwrap_or(default)
+    };
+    (read("COLUMNS", 80), read("LINES", 24))
+}
+
+impl AsciiDrawer {
+    pub fn new() -> Self {
+        AsciiDrawer {
+            canvas: AsciiCanvas::new(),
+            scale: [1., 1.].into(),
+            history: Vec::new(),
+            viewport: None,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            y_axis: YAxis::default(),
+            axes: false,
+            padding: Spacing::default(),
+            include_origin: false,
+            fit: None,
+            extent: AABB::EMPTY,
+            overhang: (0, 0),
+            aspect_ratio: None,
+            fitted: OnceCell::new(),
+        }
+    }
+
+    /// A drawer that maps world coordinates to cells by `scale`, before the
+    /// aspect ratio correction if one is set.
+    pub fn with_scale(scale: Vec2) -> Self {
+        AsciiDrawer {
+            canvas: AsciiCanvas::new(),
+            scale,
+            history: Vec::new(),
+            viewport: None,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            y_axis: YAxis::default(),
+            axes: false,
+            padding: Spacing::default(),
+            include_origin: false,
+            fit: None,
+            extent: AABB::EMPTY,
+            overhang: (0, 0),
+            aspect_ratio: None,
+            fitted: OnceCell::new(),
+        }
+    }
+
+    /// Applies `transform` to everything drawn until the matching
+    /// `pop_transform`, on top of the transforms already pushed.
+    pub fn push_transform(&mut self, transform: Transform) -> &mut Self {
+        self.transform_stack.push(self.transform);
+        self.transform = self.transform * transform;
+        self
+    }
+
+    pub fn pop_transform(&mut self) -> &mut Self {
+        self.transform = self.transform_stack.pop().unwrap_or(Transform::IDENTITY);
+        self
+    }
+
+    /// The combined transform from the current local frame to world coordinates.
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// The world size of a rect of local `size`, as if it were upright:
+    /// text is never rotated.
+    fn upright_size(&self, size: Vec2) -> Vec2 {
+        let m = self.transform.matrix;
+        [
+            size.x.abs() * m[0][0].hypot(m[1][0]),
+            size.y.abs() * m[0][1].hypot(m[1][1]),
+        ]
+        .into()
+    }
+
+    fn to_world(&self, points: &[Vec2]) -> Vec<Vec2> {
+        points
+            .iter()
+            .map(|&point| self.transform.apply(point))
+            .collect()
+    }
+
+    /// Shows only `viewport.window`, stretched over the requested number of
+    /// cells, instead of everything at the drawer's own scale. Applies to
+    /// what was already drawn as well.
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) -> &mut Self {
+        self.viewport = viewport;
+        self.rebuild();
+        self
+    }
+
+    /// Sets which way +y points. Applies to what was already drawn as well.
+    pub fn set_y_axis(&mut self, y_axis: YAxis) -> &mut Self {
+        self.y_axis = y_axis;
+        self.rebuild();
+        self
+    }
+
+    /// Draws the world x and y axes beneath the drawing, see [`AsciiCanvas::set_axes`].
+    pub fn set_axes(&mut self, enabled: bool) -> &mut Self {
+        self.axes = enabled;
+        self.canvas.set_axes(enabled);
+        self.fitted.take();
+        self
+    }
+
+    /// See [`AsciiCanvas::set_padding`].
+    pub fn set_padding(&mut self, padding: Spacing) -> &mut Self {
+        self.padding = padding;
+        self.canvas.set_padding(padding);
+        self.fitted.take();
+        self
+    }
+
+    /// See [`AsciiCanvas::set_include_origin`].
+    pub fn set_include_origin(&mut self, enabled: bool) -> &mut Self {
+        self.include_origin = enabled;
+        self.canvas.set_include_origin(enabled);
+        self.fitted.take();
+        self
+    }
+
+    /// Sets how many times taller than wide a cell is displayed. The y axis
+    /// is then compressed by that much so that a uniform `scale` draws
+    /// squares as squares, and slanted lines pick their glyphs to match.
+    /// Until it is set, `scale` maps world units to cells as it is, while
+    /// `set_fit` and the glyphs assume cells twice as tall as wide.
+    /// Applies to what was already drawn as well.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) -> &mut Self {
+        self.aspect_ratio = Some(aspect_ratio);
+        self.rebuild();
+        self
+    }
+
+    /// How many times taller than wide cells are taken to be.
+    fn cell_aspect(&self) -> f32 {
+        self.aspect_ratio.unwrap_or(CELL_ASPECT)
+    }
+
+    /// The drawer's own scale, compressed along y by the aspect ratio if set.
+    fn own_scale(&self) -> Vec2 {
+        [
+            self.scale.x,
+            self.scale.y / self.aspect_ratio.unwrap_or(1.0),
+        ]
+        .into()
+    }
+
+    /// Picks the scale that makes everything drawn fit in `columns` x `rows`
+    /// cells, keeping circles round, instead of using the drawer's own scale.
+    /// The scale is recomputed as the drawing grows.
+    pub fn set_fit(&mut self, budget: Option<(u32, u32)>) -> &mut Self {
+        self.fit = budget;
+        self.rebuild();
+        self
+    }
+
+    /// Fits the drawing to the size of the terminal, see [`terminal_size`].
+    pub fn fit_to_terminal(&mut self) -> &mut Self {
+        // Leave a row for the prompt that follows the output.
+        let (columns, rows) = terminal_size();
+        self.set_fit(Some((columns, rows.saturating_sub(1).max(1))))
+    }
+
+    /// The scale from world coordinates to cells currently in effect.
+    fn view_scale(&self) -> Vec2 {
+        if let (None, Some((columns, rows))) = (self.viewport, self.fit) {
+            if self.extent.is_empty() {
+                return self.own_scale();
+            }
+            let width = self.extent.size().x;
+            let height = self.extent.size().y / self.cell_aspect();
+            // Rounding to whole cells can push each side of the drawing out
+            // by up to a cell and a half, so that much is kept in reserve.
+            let fit = |cells: u32, extent: f32| {
+                if extent > 0.0 {
+                    cells.saturating_sub(3) as f32 / extent
+                } else {
+                    f32::INFINITY
+                }
+            };
+            let columns = columns.saturating_sub(2 * self.overhang.0).max(4);
+            let rows = rows.saturating_sub(2 * self.overhang.1).max(4);
+            let uniform = fit(columns, width).min(fit(rows, height));
+            let uniform = if uniform.is_finite() { uniform } else { 1.0 };
+            return [uniform, uniform / self.cell_aspect()].into();
+        }
+
+        match self.viewport {
+            Some(viewport) => {
+                let width = viewport.window.x_max - viewport.window.x_min;
+                let height = viewport.window.y_max - viewport.window.y_min;
+                let fit = |cells: u32, extent: f32| {
+                    if extent > 0.0 {
+                        cells.saturating_sub(1).max(1) as f32 / extent
+                    } else {
+                        1.0
+                    }
+                };
+                [fit(viewport.columns, width), fit(viewport.rows, height)].into()
+            }
+            None => self.own_scale(),
+        }
+    }
+
+    /// Whether the scale is picked to fit the drawing, see [`Self::set_fit`].
+    fn fitting(&self) -> bool {
+        self.viewport.is_none() && self.fit.is_some()
+    }
+
+    /// Draws the whole history again on a fresh canvas, after the view changed.
+    fn rebuild(&mut self) {
+        self.fitted.take();
+        self.canvas = self.replay(!self.fitting());
+    }
+
+    /// A fresh canvas set up for the current view, with the whole history
+    /// replayed on it, leaving out what the commands draw unless `draw`.
+    fn replay(&self, draw: bool) -> AsciiCanvas {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .set_y_axis(self.y_axis)
+            .set_axes(self.axes)
+            .set_padding(self.padding)
+            .set_include_origin(self.include_origin)
+            .set_aspect_ratio(self.cell_aspect());
+
+        let scale = self.view_scale();
+        if let Some(viewport) = self.viewport {
+            let left = (viewport.window.x_min * scale.x).round();
+            let bottom = (viewport.window.y_min * scale.y).round();
+            canvas
+                .set_clip(Some(AABB {
+                    x_min: left,
+                    x_max: left + viewport.columns.max(1) as f32 - 1.0,
+                    y_min: bottom,
+                    y_max: bottom + viewport.rows.max(1) as f32 - 1.0,
+                }))
+                .set_offscreen_indicators(viewport.indicators);
+        }
+
+        for command in &self.history {
+            if draw || !command.draws() {
+                command.execute(&mut canvas, scale);
+            }
+        }
+        canvas
+    }
+
+    /// The canvas holding the drawing as it is shown.
+    fn view(&self) -> &AsciiCanvas {
+        if self.fitting() {
+            self.fitted.get_or_init(|| self.replay(true))
+        } else {
+            &self.canvas
+        }
+    }
+
+    /// Every call made on this drawer so far, in order.
+    pub fn history(&self) -> &[DrawCommand] {
+        &self.history
+    }
+
+    fn record(&mut self, command: DrawCommand) -> &mut Self {
+        let (columns, rows) = command.overhang(self.canvas.text_layout.line_spacing);
+        self.overhang = (self.overhang.0.max(columns), self.overhang.1.max(rows));
+        self.extent = self.extent.union(&command.extent());
+
+        // The fitted scale depends on everything drawn, so drawing waits
+        // until the output is asked for instead of starting over each time
+        // the drawing grows.
+        if self.fitting() {
+            self.fitted.take();
+        }
+        if !self.fitting() || !command.draws() {
+            let scale = self.view_scale();
+            command.execute(&mut self.canvas, scale);
+        }
+        self.history.push(command);
+        self
+    }
+
+    pub fn rect(&mut self, center: Vec2, size: Vec2) -> &mut Self {
+        if !self.transform.is_axis_aligned() {
+            let (half_width, half_height) = (size.x / 2.0, size.y / 2.0);
+            let corners = [
+                [center.x - half_width, center.y - half_height].into(),
+                [center.x + half_width, center.y - half_height].into(),
+                [center.x + half_width, center.y + half_height].into(),
+                [center.x - half_width, center.y + half_height].into(),
+            ];
+            return self.record(DrawCommand::Polygon {
+                points: self.to_world(&corners),
+                filled: false,
+            });
+        }
+
+        let m = self.transform.matrix;
+        self.record(DrawCommand::Rect {
+            center: self.transform.apply(center),
+            size: [(size.x * m[0][0]).abs(), (size.y * m[1][1]).abs()].into(),
+        })
+    }
+
+    /// Draws `rect(center, size)` with a title and an optional footer in its
+    /// border, see [`AsciiCanvas::panel`]. Like the text in it, the panel
+    /// stays upright under a rotation.
+    pub fn panel(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        title: &str,
+        footer: Option<&str>,
+        alignment: HorizontalAnchor,
+    ) -> &mut Self {
+        self.upright_rect(center, size);
+
+        self.record(DrawCommand::PanelTitles {
+            center: self.transform.apply(center),
+            size: self.upright_size(size),
+            title: title.to_string(),
+            footer: footer.map(str::to_string),
+            alignment,
+        })
+    }
+
+    /// Draws `rect(center, size)` with `text` word-wrapped inside it, see
+    /// [`AsciiCanvas::text_in_rect`]. Like the text, the rect stays upright
+    /// under a rotation.
+    pub fn rect_with_text(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        text: &str,
+        layout: TextLayout,
+    ) -> &mut Self {
+        self.upright_rect(center, size);
+        self.record(DrawCommand::TextInRect {
+            center: self.transform.apply(center),
+            size: self.upright_size(size),
+            text: text.to_string(),
+            layout,
+        })
+    }
+
+    /// Draws `rect(center, size)` without rotating it, at the size it would
+    /// have upright, so that text written to it still lines up.
+    fn upright_rect(&mut self, center: Vec2, size: Vec2) -> &mut Self {
+        self.record(DrawCommand::Rect {
+            center: self.transform.apply(center),
+            size: self.upright_size(size),
+        })
+    }
+
+    /// Draws the outline of `aabb`, or nothing if it is empty.
+    pub fn aabb(&mut self, aabb: &AABB) -> &mut Self {
+        if aabb.is_empty() {
+            return self;
+        }
+        self.rect(aabb.center(), aabb.size())
+    }
+
+    pub fn text(&mut self, position: Vec2, text: &str) -> &mut Self {
+        self.record(DrawCommand::Text {
+            position: self.transform.apply(position),
+            text: text.to_string(),
+        })
+    }
+
+    pub fn rect_styled(&mut self, center: Vec2, size: Vec2, style: Style) -> &mut Self {
+        self.with_style(style, |drawer| drawer.rect(center, size))
+    }
+
+    pub fn text_styled(&mut self, position: Vec2, text: &str, style: Style) -> &mut Self {
+        self.with_style(style, |drawer| drawer.text(position, text))
+    }
+
+    pub fn rect_with_labels_styled(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        corners_coords: bool,
+        center_coords: bool,
+        edge_lengths: bool,
+        style: Style,
+    ) -> &mut Self {
+        self.with_style(style, |drawer| {
+            drawer.rect_with_labels(center, size, corners_coords, center_coords, edge_lengths)
+        })
+    }
+
+    pub fn text_with_layout(
+        &mut self,
+        position: Vec2,
+        text: &str,
+        layout: TextLayout,
+    ) -> &mut Self {
+        let previous = self.canvas.text_layout;
+        self.set_text_layout(layout);
+        self.text(position, text);
+        self.set_text_layout(previous)
+    }
+
+    /// Writes `text` in big letters made of `font`'s glyphs, anchored at
+    /// `position` like any other text.
+    pub fn banner(&mut self, position: Vec2, text: &str, font: &FigletFont) -> &mut Self {
+        // Glyph rows are stacked without gaps, whatever the current spacing.
+        let layout = TextLayout {
+            line_spacing: 0,
+            ..self.canvas.text_layout
+        };
+        self.text_with_layout(position, &font.render(text).join("\n"), layout)
+    }
+
+    fn with_style(&mut self, style: Style, draw: impl FnOnce(&mut Self) -> &mut Self) -> &mut Self {
+        let previous = self.canvas.style;
+        self.set_style(style);
+        draw(self);
+        self.set_style(previous)
+    }
+
+    pub fn rect_with_labels(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        corners_coords: bool,
+        center_coords: bool,
+        edge_lengths: bool,
+    ) -> &mut Self {
+        self.rect(center, size);
+
+        // Labels live on their own layer so that later shapes cannot erase them.
+        let previous = &self.canvas.layers[self.canvas.current_layer];
+        let (previous_name, previous_z) = (previous.name.clone(), previous.z);
+        let labels_z = self.canvas.layer_z(LABELS_LAYER).unwrap_or(LABELS_Z);
+        self.layer(LABELS_LAYER, labels_z);
+        // Labels are centered on what they describe, whatever the text layout.
+        let previous_layout = self.canvas.text_layout;
+        if previous_layout != TextLayout::default() {
+            self.set_text_layout(TextLayout::default());
+        }
+
+        let half_width = size.x / 2.0;
+        let half_height = size.y / 2.0;
+
+        let corners = [
+            [center.x - half_width, center.y - half_height].into(),
+            [center.x + half_width, center.y - half_height].into(),
+            [center.x - half_width, center.y + half_height].into(),
+            [center.x + half_width, center.y + half_height].into(),
+        ];
+
+        if corners_coords {
+            for corner in self.to_world(&corners) {
+                self.record(DrawCommand::PointLabel { point: corner });
+            }
+        }
+
+        if center_coords {
+            let point = self.transform.apply(center);
+            self.record(DrawCommand::PointLabel { point });
+        }
+
+        if edge_lengths {
+            let left_center: Vec2 = [center.x - half_width, center.y].into();
+            let bottom_center: Vec2 = [center.x, center.y - half_height].into();
+            let edge_length_x = size.x.round() as i32;
+            let edge_length_y = size.y.round() as i32;
+
+            self.text(left_center, &edge_length_y.to_string());
+            self.text(bottom_center, &edge_length_x.to_string());
+        }
+
+        if previous_layout != TextLayout::default() {
+            self.set_text_layout(previous_layout);
+        }
+        self.layer(&previous_name, previous_z)
+    }
+
+    pub fn point(&mut self, position: Vec2) -> &mut Self {
+        let position = self.transform.apply(position);
+        self.record(DrawCommand::Point { position })
+    }
+
+    pub fn line(&mut self, from: Vec2, to: Vec2) -> &mut Self {
+        let (from, to) = (self.transform.apply(from), self.transform.apply(to));
+        self.record(DrawCommand::Line { from, to })
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32) -> &mut Self {
+        self.ellipse(center, [radius, radius].into())
+    }
+
+    pub fn filled_circle(&mut self, center: Vec2, radius: f32) -> &mut Self {
+        self.filled_ellipse(center, [radius, radius].into())
+    }
+
+    pub fn ellipse(&mut self, center: Vec2, radii: Vec2) -> &mut Self {
+        self.transformed_ellipse(center, radii, false)
+    }
+
+    pub fn filled_ellipse(&mut self, center: Vec2, radii: Vec2) -> &mut Self {
+        self.transformed_ellipse(center, radii, true)
+    }
+
+    fn transformed_ellipse(&mut self, center: Vec2, radii: Vec2, filled: bool) -> &mut Self {
+        let m = self.transform.matrix;
+        let world_radii = if self.transform.is_axis_aligned() {
+            Some([(radii.x * m[0][0]).abs(), (radii.y * m[1][1]).abs()].into())
+        } else if radii.x == radii.y
+            && (m[0][0] == m[1][1] && m[0][1] == -m[1][0]
+                || m[0][0] == -m[1][1] && m[0][1] == m[1][0])
+        {
+            // Rotating a circle, possibly mirrored, leaves it a circle.
+            let radius = radii.x * m[0][0].hypot(m[1][0]);
+            Some([radius, radius].into())
+        } else {
+            None
+        };
+
+        if let Some(radii) = world_radii {
+            let center = self.transform.apply(center);
+            return self.record(DrawCommand::Ellipse {
+                center,
+                radii,
+                filled,
+            });
+        }
+
+        // Anything else turns the ellipse around, so trace it as a polygon.
+        const SEGMENTS: usize = 64;
+        let outline: Vec<Vec2> = (0..SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                [
+                    center.x + radii.x * angle.cos(),
+                    center.y + radii.y * angle.sin(),
+                ]
+                .into()
+            })
+            .collect();
+        self.record(DrawCommand::Polygon {
+            points: self.to_world(&outline),
+            filled,
+        })
+    }
+
+    pub fn polyline(&mut self, points: &[Vec2]) -> &mut Self {
+        self.record(DrawCommand::Polyline {
+            points: self.to_world(points),
+        })
+    }
+
+    pub fn polygon(&mut self, points: &[Vec2]) -> &mut Self {
+        self.record(DrawCommand::Polygon {
+            points: self.to_world(points),
+            filled: false,
+        })
+    }
+
+    pub fn filled_polygon(&mut self, points: &[Vec2]) -> &mut Self {
+        self.record(DrawCommand::Polygon {
+            points: self.to_world(points),
+            filled: true,
+        })
+    }
+
+    pub fn set_fill_char(&mut self, fill_char: char) -> &mut Self {
+        self.record(DrawCommand::SetFillChar(fill_char))
+    }
+
+    /// Sets how the following texts are anchored and how their lines are spaced.
+    pub fn set_text_layout(&mut self, layout: TextLayout) -> &mut Self {
+        self.record(DrawCommand::SetTextLayout(layout))
+    }
+
+    pub fn set_style(&mut self, style: Style) -> &mut Self {
+        self.record(DrawCommand::SetStyle(style))
+    }
+
+    pub fn set_line_style(&mut self, line_style: LineStyle) -> &mut Self {
+        self.record(DrawCommand::SetLineStyle(line_style))
+    }
+
+    pub fn set_merge_policy(&mut self, merge_policy: MergePolicy) -> &mut Self {
+        self.record(DrawCommand::SetMergePolicy(merge_policy))
+    }
+
+    /// Sets how the following primitives are rasterized. Geometry already
+    /// drawn keeps the mode it was drawn in.
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> &mut Self {
+        self.record(DrawCommand::SetRenderMode(mode))
+    }
+
+    /// Sends the following primitives to the layer `name`, creating it if
+    /// needed, and moves that layer to z-order `z`.
+    pub fn layer(&mut self, name: &str, z: i32) -> &mut Self {
+        self.record(DrawCommand::Layer {
+            name: name.to_string(),
+            z,
+        })
+    }
+
+    /// Shows or hides the layer `name` when rendering.
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> &mut Self {
+        self.record(DrawCommand::SetLayerVisible {
+            name: name.to_string(),
+            visible,
+        })
+    }
+
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let (canvas, scale) = (self.view(), self.view_scale());
+        let mut rects = Vec::new();
+        if options.vector_rects {
+            let mut style = Style::default();
+            let mut layer = DEFAULT_LAYER;
+            for command in &self.history {
+                match command {
+                    DrawCommand::SetStyle(new_style) => style = *new_style,
+                    DrawCommand::Layer { name, .. } => layer = name,
+                    DrawCommand::Rect { center, size } if canvas.layer_visible(layer) => {
+                        let center = canvas.oriented(*center * scale);
+                        let frame = rect_frame(center, *size * scale);
+                        rects.push((frame, style));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        svg::render(canvas, options, &rects)
+    }
+
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let (canvas, scale) = (self.view(), self.view_scale());
+        let mut tooltips = HashMap::new();
+        if options.coordinate_tooltips {
+            let mut layer = DEFAULT_LAYER;
+            for command in &self.history {
+                match command {
+                    DrawCommand::Layer { name, .. } => layer = name,
+                    DrawCommand::PointLabel { point } if canvas.layer_visible(layer) => {
+                        let title = format!("({}, {})", point.x, point.y);
+                        let position = canvas.oriented(*point * scale);
+                        let label = point.to_string();
+                        for (cell, _) in layout_text(position, &label, &TextLayout::default()) {
+                            tooltips.insert(cell, title.clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        html::render(canvas, options, &tooltips)
+    }
+
+    pub fn to_png(&self, options: &PngOptions) -> Vec<u8> {
+        self.view().to_png(options)
+    }
+
+    pub fn write_png(&self, out: &mut impl io::Write, options: &PngOptions) -> io::Result<()> {
+        self.view().write_png(out, options)
+    }
+
+    pub fn canvas(&self) -> &AsciiCanvas {
+        self.view()
+    }
+
+    pub fn render_with(&self, options: &RenderOptions) -> String {
+        self.view().render_with(options)
+    }
+
+    pub fn render_to_string(&self) -> String {
+        self.view().render_to_string()
+    }
+
+    pub fn write_with(&self, out: &mut impl io::Write, options: &RenderOptions) -> io::Result<()> {
+        self.view().write_with(out, options)
+    }
+
+    pub fn write_to(&self, out: &mut impl io::Write) -> io::Result<()> {
+        self.view().write_to(out)
+    }
+
+    pub fn draw(&self) {
+        self.view().draw();
+    }
+}
+
+fn scaled(points: &[Vec2], scale: Vec2) -> Vec<Vec2> {
+    points.iter().map(|&point| point * scale).collect()
+}
+
+impl fmt::Display for AsciiDrawer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+impl Default for AsciiDrawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn main() {
     AsciiDrawer::with_scale([4.5, 2.0].into())
         .rect([-1.0, 0.0].into(), [1.0, 1.0].into())
         .rect_with_labels([0.0, 0.0].into(), [10.0, 5.0].into(), true, false, false)
@@ -238,3 +2851,483 @@ fn main() {
         .text([0., 2.5].into(), "Hello World!")
         .draw();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(canvas: &AsciiCanvas) -> Vec<String> {
+        canvas
+            .render_to_string()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!(
+            (actual - expected).length() < 1e-5,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn vec2_rotates_counter_clockwise() {
+        let quarter = std::f32::consts::FRAC_PI_2;
+        assert_close(Vec2::new(1.0, 0.0).rotate(quarter), Vec2::new(0.0, 1.0));
+        assert_close(Vec2::new(0.0, 2.0).rotate(quarter), Vec2::new(-2.0, 0.0));
+        assert_close(Vec2::new(3.0, 4.0).rotate(-quarter), Vec2::new(4.0, -3.0));
+        assert_close(Vec2::new(3.0, 4.0).rotate(0.0), Vec2::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn vec2_lerp_normalize_and_cross() {
+        let (a, b) = (Vec2::new(-2.0, 4.0), Vec2::new(6.0, 0.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.25), Vec2::new(0.0, 3.0));
+        assert_eq!(a.lerp(b, 1.5), Vec2::new(10.0, -2.0));
+
+        assert_close(Vec2::new(3.0, 4.0).normalize(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::ZERO.normalize(), Vec2::ZERO);
+
+        let (x, y) = (Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0));
+        assert_eq!(x.cross(y), 1.0);
+        assert_eq!(y.cross(x), -1.0);
+        assert_eq!(x.cross(x * 3.0), 0.0);
+    }
+
+    #[test]
+    fn vec2_converts_from_arrays_and_tuples() {
+        assert_eq!(Vec2::from([1.5, -2.0]), Vec2::new(1.5, -2.0));
+        assert_eq!(Vec2::from((1.5, -2.0)), Vec2::new(1.5, -2.0));
+        assert_eq!(Vec2::from([3, -4]), Vec2::new(3.0, -4.0));
+    }
+
+    #[test]
+    fn aabb_union_and_intersection() {
+        let a = AABB::new([0.0, 0.0].into(), [4.0, 2.0].into());
+        let b = AABB::new([2.0, 1.0].into(), [6.0, 5.0].into());
+        assert_eq!(a.union(&b), AABB::new([0.0, 0.0].into(), [6.0, 5.0].into()));
+        assert_eq!(
+            a.intersection(&b),
+            AABB::new([2.0, 1.0].into(), [4.0, 2.0].into())
+        );
+
+        // EMPTY is the identity of union and absorbs intersections.
+        assert_eq!(AABB::EMPTY.union(&a), a);
+        assert_eq!(a.union(&AABB::EMPTY), a);
+        assert!(AABB::EMPTY.union(&AABB::EMPTY).is_empty());
+        assert!(a.intersection(&AABB::EMPTY).is_empty());
+
+        let apart = AABB::new([10.0, 10.0].into(), [11.0, 11.0].into());
+        assert_eq!(a.intersection(&apart), AABB::EMPTY);
+    }
+
+    #[test]
+    fn aabb_overlaps_including_touching_edges() {
+        let a = AABB::new([0.0, 0.0].into(), [4.0, 2.0].into());
+        assert!(a.overlaps(&AABB::new([1.0, 1.0].into(), [2.0, 5.0].into())));
+        assert!(a.overlaps(&AABB::new([4.0, 0.0].into(), [5.0, 1.0].into())));
+        assert!(a.overlaps(&AABB::new([4.0, 2.0].into(), [5.0, 3.0].into())));
+        assert!(!a.overlaps(&AABB::new([4.5, 0.0].into(), [5.0, 1.0].into())));
+        assert!(!a.overlaps(&AABB::EMPTY));
+        assert!(!AABB::EMPTY.overlaps(&AABB::EMPTY));
+    }
+
+    #[test]
+    fn aabb_expand_and_empty_state() {
+        let a = AABB::new([0.0, 0.0].into(), [4.0, 2.0].into());
+        assert_eq!(
+            a.expand(1.0),
+            AABB::new([-1.0, -1.0].into(), [5.0, 3.0].into())
+        );
+        assert_eq!(
+            a.expand(-0.5),
+            AABB::new([0.5, 0.5].into(), [3.5, 1.5].into())
+        );
+        assert!(a.expand(-2.0).is_empty());
+        assert_eq!(AABB::EMPTY.expand(1.0), AABB::EMPTY);
+
+        assert!(AABB::EMPTY.is_empty());
+        assert!(!AABB::from_points([Vec2::new(1.0, 1.0)]).is_empty());
+        assert_eq!(AABB::EMPTY.size(), Vec2::ZERO);
+        assert_eq!(AABB::EMPTY.center(), Vec2::ZERO);
+        assert_eq!(a.center(), Vec2::new(2.0, 1.0));
+        assert_eq!(a.size(), Vec2::new(4.0, 2.0));
+    }
+
+    #[test]
+    fn glyph_picks_junctions_from_strokes() {
+        let light = LineStyle::Light;
+        assert_eq!(light.glyph(STROKE_VERTICAL), '│');
+        assert_eq!(light.glyph(STROKE_HORIZONTAL), '─');
+        assert_eq!(light.glyph(STROKE_DOWN | STROKE_RIGHT), '┌');
+        assert_eq!(light.glyph(STROKE_UP | STROKE_LEFT), '┘');
+        assert_eq!(light.glyph(STROKE_VERTICAL | STROKE_RIGHT), '├');
+        assert_eq!(light.glyph(STROKE_HORIZONTAL | STROKE_UP), '┴');
+        assert_eq!(light.glyph(STROKE_ORTHOGONAL), '┼');
+        assert_eq!(
+            LineStyle::Double.glyph(STROKE_HORIZONTAL | STROKE_DOWN),
+            '╦'
+        );
+        assert_eq!(LineStyle::Rounded.glyph(STROKE_UP | STROKE_RIGHT), '╰');
+        assert_eq!(LineStyle::Ascii.glyph(STROKE_DOWN | STROKE_LEFT), '+');
+    }
+
+    #[test]
+    fn glyph_extends_lone_strokes_and_diagonals() {
+        assert_eq!(LineStyle::Heavy.glyph(STROKE_UP), '┃');
+        assert_eq!(LineStyle::Heavy.glyph(STROKE_LEFT), '━');
+        assert_eq!(LineStyle::Ascii.glyph(STROKE_RISING), '/');
+        assert_eq!(LineStyle::Ascii.glyph(STROKE_FALLING), '\\');
+        assert_eq!(LineStyle::Ascii.glyph(STROKE_DIAGONAL), 'X');
+        assert_eq!(LineStyle::Light.glyph(STROKE_DIAGONAL), '╳');
+    }
+
+    #[test]
+    fn crossing_lines_merge_into_junctions() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .set_line_style(LineStyle::Light)
+            .line([-2.0, 0.0].into(), [2.0, 0.0].into())
+            .line([0.0, -1.0].into(), [0.0, 1.0].into())
+            .line([2.0, 0.0].into(), [2.0, 1.0].into());
+        assert_eq!(lines(&canvas), ["  │ │", "──┼─┘", "  │  "]);
+    }
+
+    #[test]
+    fn polygon_vertices_keep_apexes_while_crossings_merge() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .polygon(&[
+                [0.0, 2.0].into(),
+                [2.0, 0.0].into(),
+                [0.0, -2.0].into(),
+                [-2.0, 0.0].into(),
+            ])
+            .line([4.0, -2.0].into(), [8.0, 2.0].into())
+            .line([4.0, 2.0].into(), [8.0, -2.0].into());
+        assert_eq!(
+            lines(&canvas),
+            [
+                r"  ^   \   /",
+                r" / \   \ / ",
+                r"<   >   X  ",
+                r" \ /   / \ ",
+                r"  v   /   \",
+            ]
+        );
+    }
+
+    fn viewport() -> Viewport {
+        Viewport {
+            window: AABB::new([0.0, 0.0].into(), [10.0, 5.0].into()),
+            columns: 21,
+            rows: 6,
+            indicators: true,
+        }
+    }
+
+    #[test]
+    fn viewport_drops_cells_outside_the_window() {
+        let mut drawer = AsciiDrawer::new();
+        drawer
+            .set_viewport(Some(viewport()))
+            .line([-5.0, 1.0].into(), [15.0, 1.0].into());
+        let output = drawer.render_to_string();
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows.len(), 6);
+        assert!(rows.iter().all(|row| row.chars().count() == 21));
+        assert_eq!(rows[4], "-".repeat(21));
+    }
+
+    #[test]
+    fn viewport_points_at_shapes_outside_the_window() {
+        let mut drawer = AsciiDrawer::new();
+        drawer
+            .set_viewport(Some(viewport()))
+            .rect([30.0, 2.0].into(), [2.0, 2.0].into())
+            .rect([5.0, 20.0].into(), [2.0, 2.0].into());
+        assert_eq!(
+            drawer.render_to_string(),
+            [
+                "          ^          ",
+                "                     ",
+                "                     ",
+                "                    >",
+                "                     ",
+                "                     ",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn viewport_has_no_indicator_for_partly_visible_shapes() {
+        let mut drawer = AsciiDrawer::new();
+        drawer
+            .set_viewport(Some(viewport()))
+            .rect([10.0, 2.0].into(), [4.0, 2.0].into());
+        let output = drawer.render_to_string();
+        assert!(output.contains("+----"), "{output}");
+        assert!(!output.contains(['<', '>', '^', 'v']), "{output}");
+    }
+
+    #[test]
+    fn strokes_over_half_a_wide_character_keep_the_row_aligned() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .text([0.0, 0.0].into(), "漢字ab")
+            .line([-2.0, -1.0].into(), [-2.0, 1.0].into())
+            .line([-1.0, -1.0].into(), [-1.0, 0.0].into());
+        assert_eq!(lines(&canvas), [" |    ", " || ab", " ||   "]);
+    }
+
+    #[test]
+    fn panels_and_boxed_text_stay_upright_under_rotation() {
+        let mut drawer = AsciiDrawer::new();
+        drawer
+            .push_transform(Transform::rotation(std::f32::consts::FRAC_PI_6))
+            .panel(
+                [0.0, 0.0].into(),
+                [16.0, 4.0].into(),
+                "Title",
+                Some("end"),
+                HorizontalAnchor::Center,
+            )
+            .rect_with_text(
+                [0.0, -8.0].into(),
+                [12.0, 4.0].into(),
+                "some wrapped words",
+                TextLayout::default(),
+            );
+        let output = drawer.render_to_string();
+        assert!(output.contains("+---- Title ----+"), "{output}");
+        assert!(output.contains("+----- end -----+"), "{output}");
+        assert!(output.contains("|  wrapped  |"), "{output}");
+        assert!(!output.contains(['/', '\\']), "{output}");
+    }
+
+    #[test]
+    fn switching_render_mode_keeps_earlier_geometry() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .set_render_mode(RenderMode::Braille)
+            .circle([0.0, 0.0].into(), 3.0)
+            .set_render_mode(RenderMode::Text)
+            .rect([10.0, 0.0].into(), [4.0, 2.0].into())
+            .set_render_mode(RenderMode::HalfBlock)
+            .line([-3.0, -5.0].into(), [3.0, -5.0].into());
+        let output = canvas.render_to_string();
+
+        assert!(output.contains('+'));
+        assert!(output.contains('▄') || output.contains('▀'));
+        let braille = output
+            .chars()
+            .filter(|ch| ('\u{2801}'..='\u{28FF}').contains(ch))
+            .count();
+        assert!(braille > 10, "{output}");
+    }
+
+    #[test]
+    fn exports_skip_hidden_layers() {
+        let mut drawer = AsciiDrawer::with_scale([2.0, 1.0].into());
+        drawer
+            .layer("grid", -1)
+            .rect([0.0, 0.0].into(), [20.0, 10.0].into())
+            .layer(DEFAULT_LAYER, 0)
+            .rect_with_labels([0.0, 0.0].into(), [4.0, 4.0].into(), true, false, false)
+            .set_layer_visible("grid", false);
+        let options = SvgOptions {
+            vector_rects: true,
+            ..SvgOptions::default()
+        };
+        assert_eq!(drawer.to_svg(&options).matches("fill=\"none\"").count(), 1);
+
+        assert!(drawer.to_html(&HtmlOptions::default()).contains("title="));
+        drawer.set_layer_visible(LABELS_LAYER, false);
+        assert!(!drawer.to_html(&HtmlOptions::default()).contains("title="));
+    }
+
+    #[test]
+    fn only_similarity_transforms_keep_circles() {
+        let ellipse = |transform: Transform| {
+            let mut drawer = AsciiDrawer::new();
+            drawer
+                .push_transform(transform)
+                .circle([0.0, 0.0].into(), 1.0);
+            match drawer.history() {
+                [DrawCommand::Ellipse { radii, .. }] => Some(*radii),
+                _ => None,
+            }
+        };
+        let rotated = ellipse(Transform::rotation(0.5) * Transform::scaling([2.0, 2.0].into()));
+        assert!(rotated.is_some_and(|radii| (radii.x - 2.0).abs() < 1e-5 && radii.x == radii.y));
+        let mirrored = Transform {
+            matrix: [[0.6, 0.8, 0.0], [0.8, -0.6, 0.0]],
+        };
+        assert!(ellipse(mirrored).is_some());
+
+        let sheared = Transform {
+            matrix: [[2.0, 1.0, 0.0], [1.0, 2.0, 0.0]],
+        };
+        assert_eq!(ellipse(sheared), None);
+    }
+
+    #[test]
+    fn fitting_before_or_after_drawing_gives_the_same_output() {
+        let draw = |drawer: &mut AsciiDrawer| {
+            drawer
+                .set_line_style(LineStyle::Light)
+                .rect([0.0, 0.0].into(), [10.0, 5.0].into())
+                .circle([20.0, 3.0].into(), 4.0)
+                .text([0.0, 0.0].into(), "a\nb")
+                .line([-5.0, -5.0].into(), [30.0, 10.0].into());
+        };
+        let mut before = AsciiDrawer::new();
+        before.set_fit(Some((40, 12)));
+        draw(&mut before);
+        let mut after = AsciiDrawer::new();
+        draw(&mut after);
+        after.set_fit(Some((40, 12)));
+        assert_eq!(before.render_to_string(), after.render_to_string());
+        assert!(before.render_to_string().contains('┌'));
+    }
+
+    #[test]
+    fn fitted_output_stays_within_the_budget() {
+        let shapes: [&dyn Fn(&mut AsciiDrawer); 6] = [
+            &|drawer| {
+                drawer.rect([0.0, 0.0].into(), [100.0, 50.0].into());
+            },
+            &|drawer| {
+                drawer.rect([0.3, -0.2].into(), [100.0, 5.0].into());
+            },
+            &|drawer| {
+                drawer
+                    .circle([1.0, 1.0].into(), 7.0)
+                    .line([-9.0, 0.0].into(), [3.0, 11.0].into());
+            },
+            &|drawer| {
+                drawer
+                    .rect_with_labels([0.0, 0.0].into(), [10.0, 5.0].into(), true, false, false)
+                    .text([0.0, 2.5].into(), "Hello World!");
+            },
+            &|drawer| {
+                drawer.rect([0.0, 0.0].into(), [100.0, 50.0].into()).banner(
+                    [0.0, 20.0].into(),
+                    "Hi",
+                    &FigletFont::bundled(),
+                );
+            },
+            &|drawer| {
+                drawer
+                    .rect([0.0, 0.0].into(), [10.0, 5.0].into())
+                    .text([0.0, 0.0].into(), &"\n".repeat(69));
+            },
+        ];
+
+        for shape in shapes {
+            for columns in [30, 40, 41, 80] {
+                for rows in [7, 10, 11, 24, 50] {
+                    let mut drawer = AsciiDrawer::new();
+                    drawer.set_fit(Some((columns, rows)));
+                    shape(&mut drawer);
+                    let output = drawer.render_to_string();
+                    let lines: Vec<&str> = output.lines().collect();
+                    assert!(lines.len() <= rows as usize, "{columns}x{rows}\n{output}");
+                    for line in lines {
+                        assert!(
+                            line.chars().count() <= columns as usize,
+                            "{columns}x{rows}\n{output}"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Multi-line text only holds back room for its widest line.
+        let mut drawer = AsciiDrawer::new();
+        drawer
+            .set_fit(Some((80, 24)))
+            .rect([0.0, 0.0].into(), [100.0, 50.0].into())
+            .banner([0.0, 20.0].into(), "Hi", &FigletFont::bundled());
+        let output = drawer.render_to_string();
+        assert!(output.lines().count() > 12, "{output}");
+    }
+
+    #[test]
+    fn aspect_ratio_is_opt_in_and_reaches_the_glyphs() {
+        let mut drawer = AsciiDrawer::with_scale([1.0, 1.0].into());
+        drawer
+            .rect([0.0, 0.0].into(), [4.0, 10.0].into())
+            .line([10.0, 0.0].into(), [16.0, 2.0].into());
+        assert_eq!(drawer.render_to_string().lines().count(), 11);
+        assert!(drawer.render_to_string().contains('/'));
+
+        drawer.set_aspect_ratio(2.0);
+        assert_eq!(drawer.render_to_string().lines().count(), 7);
+
+        // A shallow slope on square cells stays horizontal.
+        drawer.set_aspect_ratio(1.0);
+        assert_eq!(drawer.render_to_string().lines().count(), 11);
+        assert!(!drawer.render_to_string().contains('/'));
+    }
+
+    #[test]
+    fn wrap_text_breaks_between_words() {
+        assert_eq!(
+            wrap_text("the quick  brown fox", 10, 5),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(wrap_text("a\n\nb", 5, 5), ["a", "", "b"]);
+        assert!(wrap_text("anything", 0, 5).is_empty());
+        assert!(wrap_text("anything", 5, 0).is_empty());
+    }
+
+    #[test]
+    fn wrap_text_hyphenates_long_words() {
+        assert_eq!(wrap_text("abcdefghij", 4, 5), ["abc-", "def-", "ghi-", "j"]);
+        assert_eq!(wrap_text("to abcdef", 4, 5), ["to", "abc-", "def"]);
+        // A single column has no room for a hyphen.
+        assert_eq!(wrap_text("abc", 1, 5), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn wrap_text_ends_truncated_text_with_an_ellipsis() {
+        assert_eq!(wrap_text("one two three four", 9, 1), ["one tw..."]);
+        assert_eq!(
+            wrap_text("one two three four", 7, 2),
+            ["one two", "thre..."]
+        );
+        assert_eq!(wrap_text("aa bb cc", 3, 2), ["aa", "..."]);
+        assert_eq!(wrap_text("aa bb cc", 2, 1), [".."]);
+        assert_eq!(wrap_text("aa bb cc", 1, 1), ["."]);
+    }
+
+    #[test]
+    fn wrap_text_measures_wide_graphemes() {
+        assert_eq!(
+            wrap_text("日本語テキスト", 6, 5),
+            ["日本-", "語テ-", "キス-", "ト"]
+        );
+        assert_eq!(wrap_text("日本 語", 4, 5), ["日本", "語"]);
+        assert_eq!(
+            wrap_text("e\u{301}e\u{301}e\u{301}", 2, 5),
+            ["e\u{301}-", "e\u{301}-", "e\u{301}"]
+        );
+        assert_eq!(wrap_text("日本語テキスト", 5, 1), ["日..."]);
+    }
+
+    #[test]
+    fn overwrite_policy_keeps_the_last_stroke() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .set_merge_policy(MergePolicy::Overwrite)
+            .line([-1.0, 0.0].into(), [1.0, 0.0].into())
+            .line([0.0, -1.0].into(), [0.0, 1.0].into());
+        assert_eq!(lines(&canvas), [" | ", "-|-", " | "]);
+    }
+}
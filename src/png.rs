@@ -0,0 +1,721 @@
+use std::io;
+
+use crate::{
+    AsciiCanvas, Cell, Color, QUADRANTS, STROKE_DOWN, STROKE_DOWN_LEFT, STROKE_DOWN_RIGHT,
+    STROKE_LEFT, STROKE_RIGHT,
+};
+use crate::{STROKE_UP, STROKE_UP_LEFT, STROKE_UP_RIGHT, WIDE_CONTINUATION};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PngOptions {
+    /// Width of a cell in pixels.
+    pub cell_width: u32,
+    /// Height of a cell in pixels.
+    pub cell_height: u32,
+    /// Color of cells that have no foreground color of their own.
+    pub foreground: Color,
+    /// Color of cells that have no background color of their own.
+    pub background: Color,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            cell_width: 12,
+            cell_height: 18,
+            foreground: Color::Black,
+            background: Color::BrightWhite,
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 3;
+            self.pixels[i..i + 3].copy_from_slice(&[color.0, color.1, color.2]);
+        }
+    }
+}
+
+fn mix(a: Rgb, b: Rgb) -> Rgb {
+    let avg = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    (avg(a.0, b.0), avg(a.1, b.1), avg(a.2, b.2))
+}
+
+/// Glyph pixels of `ch` for a cell of the given size, as (x, y) offsets.
+fn glyph_pixels(ch: char, cell_width: usize, cell_height: usize) -> Vec<(usize, usize)> {
+    let mut pixels = Vec::new();
+    let code = ch as u32;
+
+    if (0x2800..=0x28FF).contains(&code) {
+        // Braille dots, numbered down the left column and then down the right
+        // one, with the bottom row (dots 7 and 8) last.
+        const DOTS: [(usize, usize); 8] = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (0, 3),
+            (1, 3),
+        ];
+        let (dot_width, dot_height) = ((cell_width / 4).max(1), (cell_height / 8).max(1));
+        for (bit, (column, row)) in DOTS.iter().enumerate() {
+            if code & (1 << bit) == 0 {
+                continue;
+            }
+            let left = column * cell_width / 2 + cell_width / 8;
+            let top = row * cell_height / 4 + cell_height / 16;
+            for y in top..top + dot_height {
+                for x in left..left + dot_width {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        return pixels;
+    }
+
+    if let Some((columns, rows, pattern)) = block_pattern(ch) {
+        for y in 0..cell_height {
+            for x in 0..cell_width {
+                let bit = (y * rows / cell_height) * columns + x * columns / cell_width;
+                if pattern & (1 << bit) != 0 {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        return pixels;
+    }
+
+    if !(0x20..0x7F).contains(&code) {
+        // Characters without a glyph are drawn as a hollow box.
+        let (right, bottom) = (cell_width * 5 / 6, cell_height * 7 / 8);
+        let (left, top) = (cell_width / 6, cell_height / 8);
+        for x in left..=right {
+            pixels.push((x, top));
+            pixels.push((x, bottom));
+        }
+        for y in top..=bottom {
+            pixels.push((left, y));
+            pixels.push((right, y));
+        }
+        return pixels;
+    }
+
+    // The 5x7 glyph sits in a 6x9 box: one column of spacing on the right,
+    // one row above and one below.
+    let rows = GLYPHS[(code - 0x20) as usize];
+    for y in 0..cell_height {
+        let glyph_y = y * 9 / cell_height;
+        if !(1..8).contains(&glyph_y) {
+            continue;
+        }
+        for x in 0..cell_width {
+            let glyph_x = x * 6 / cell_width;
+            if glyph_x < 5 && rows[glyph_y - 1] & (0x10 >> glyph_x) != 0 {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+/// The grid size and filled cells, numbered row by row from the top left,
+/// of a block element character.
+fn block_pattern(ch: char) -> Option<(usize, usize, u32)> {
+    let code = ch as u32;
+    match ch {
+        '█' => Some((1, 1, 1)),
+        '▀' => Some((1, 2, 1)),
+        '▄' => Some((1, 2, 2)),
+        '▌' => Some((2, 1, 1)),
+        '▐' => Some((2, 1, 2)),
+        _ if (0x1FB00..=0x1FB3B).contains(&code) => {
+            // Sextants skip the patterns of the half and full blocks.
+            let mut pattern = code - 0x1FB00 + 1;
+            if pattern >= 21 {
+                pattern += 1;
+            }
+            if pattern >= 42 {
+                pattern += 1;
+            }
+            Some((2, 3, pattern))
+        }
+        _ => QUADRANTS
+            .iter()
+            .position(|&quadrant| quadrant == ch)
+            .map(|pattern| (2, 2, pattern as u32)),
+    }
+}
+
+/// Pixels of the line segments that leave the cell center in the directions of `strokes`.
+fn stroke_pixels(strokes: u8, cell_width: usize, cell_height: usize) -> Vec<(usize, usize)> {
+    let mut pixels = Vec::new();
+    let (center_x, center_y) = (cell_width / 2, cell_height / 2);
+    let thickness = (cell_width.min(cell_height) / 8).max(1);
+    let band = |center: usize| center.saturating_sub(thickness / 2)..center + thickness.div_ceil(2);
+
+    let mut horizontal = |from: usize, to: usize| {
+        for x in from..to {
+            for y in band(center_y) {
+                pixels.push((x, y));
+            }
+        }
+    };
+    if strokes & STROKE_LEFT != 0 {
+        horizontal(0, center_x + 1);
+    }
+    if strokes & STROKE_RIGHT != 0 {
+        horizontal(center_x, cell_width);
+    }
+
+    let mut vertical = |from: usize, to: usize| {
+        for y in from..to {
+            for x in band(center_x) {
+                pixels.push((x, y));
+            }
+        }
+    };
+    if strokes & STROKE_UP != 0 {
+        vertical(0, center_y + 1);
+    }
+    if strokes & STROKE_DOWN != 0 {
+        vertical(center_y, cell_height);
+    }
+
+    for y in 0..cell_height {
+        let x = y * cell_width / cell_height;
+        let (upper, lower) = (y <= center_y, y >= center_y);
+        let falling =
+            upper && strokes & STROKE_UP_LEFT != 0 || lower && strokes & STROKE_DOWN_RIGHT != 0;
+        let rising =
+            upper && strokes & STROKE_UP_RIGHT != 0 || lower && strokes & STROKE_DOWN_LEFT != 0;
+        for dx in band(x) {
+            if falling {
+                pixels.push((dx, y));
+            }
+            if rising {
+                pixels.push((cell_width - 1 - dx.min(cell_width - 1), y));
+            }
+        }
+    }
+    pixels
+}
+
+fn draw_cell(image: &mut Image, origin: (usize, usize), cell: &Cell, options: &PngOptions) {
+    let (cell_width, cell_height) = (options.cell_width as usize, options.cell_height as usize);
+    let background = cell.style.bg.unwrap_or(options.background).to_rgb();
+    let mut foreground = cell.style.fg.unwrap_or(options.foreground).to_rgb();
+    if cell.style.dim {
+        foreground = mix(foreground, background);
+    }
+
+    for y in 0..cell_height {
+        for x in 0..cell_width {
+            image.set(origin.0 + x, origin.1 + y, background);
+        }
+    }
+
+    let mut pixels = if cell.strokes != 0 {
+        stroke_pixels(cell.strokes, cell_width, cell_height)
+    } else if cell.ch != ' ' && cell.ch != WIDE_CONTINUATION {
+        glyph_pixels(cell.ch, cell_width, cell_height)
+    } else {
+        Vec::new()
+    };
+    if cell.style.bold {
+        let smeared: Vec<_> = pixels.iter().map(|&(x, y)| (x + 1, y)).collect();
+        pixels.extend(smeared.into_iter().filter(|&(x, _)| x < cell_width));
+    }
+    if cell.style.underline {
+        pixels.extend((0..cell_width).map(|x| (x, cell_height - 1)));
+    }
+
+    for (x, y) in pixels {
+        image.set(origin.0 + x, origin.1 + y, foreground);
+    }
+}
+
+pub(crate) fn render(canvas: &AsciiCanvas, options: &PngOptions) -> Vec<u8> {
+    let (cell_width, cell_height) = (options.cell_width as usize, options.cell_height as usize);
+    let rows = canvas.rows();
+    let columns = rows.first().map_or(0, Vec::len);
+    let (width, height) = (columns * cell_width, rows.len() * cell_height);
+
+    // PNG has no empty images, so nothing to draw gives a single background pixel.
+    if width == 0 || height == 0 {
+        let (r, g, b) = options.background.to_rgb();
+        return encode(&Image {
+            width: 1,
+            height: 1,
+            pixels: vec![r, g, b],
+        });
+    }
+
+    let mut image = Image {
+        width,
+        height,
+        pixels: vec![0; width * height * 3],
+    };
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column, cell) in row.iter().enumerate() {
+            let origin = (column * cell_width, row_index * cell_height);
+            draw_cell(&mut image, origin, cell, options);
+        }
+    }
+
+    encode(&image)
+}
+
+pub(crate) fn write(
+    canvas: &AsciiCanvas,
+    out: &mut impl io::Write,
+    options: &PngOptions,
+) -> io::Result<()> {
+    out.write_all(&render(canvas, options))
+}
+
+fn encode(image: &Image) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // 8 bit RGB, deflate, default filtering, no interlacing.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    let stride = image.width * 3;
+    let mut scanlines = Vec::with_capacity((stride + 1) * image.height);
+    for row in image.pixels.chunks(stride.max(1)).take(image.height) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib(&scanlines));
+
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: u32,
+    pending: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.pending |= value << self.bit;
+        self.bit += count;
+        while self.bit >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.bit -= 8;
+        }
+    }
+
+    /// Huffman codes are stored starting from their most significant bit.
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Writes a literal/length symbol with the fixed Huffman code of RFC 1951.
+fn write_symbol(bits: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => bits.write_code(0x30 + symbol, 8),
+        144..=255 => bits.write_code(0x190 + symbol - 144, 9),
+        256..=279 => bits.write_code(symbol - 256, 7),
+        _ => bits.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+/// A zlib stream holding one fixed Huffman block, with a single-candidate
+/// hash lookup to find repeats. Rendered canvases are mostly runs of the
+/// same few colors, so this gets most of the benefit of a real compressor.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    const WINDOW: usize = 32 * 1024;
+    const MAX_MATCH: usize = 258;
+
+    let mut bits = BitWriter {
+        bytes: vec![0x78, 0x01],
+        bit: 0,
+        pending: 0,
+    };
+    // Final block, fixed Huffman codes.
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let hash = |i: usize| {
+        let key = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+        key.wrapping_mul(2_654_435_761) >> 16 & 0xFFFF
+    };
+    let mut last_seen = vec![usize::MAX; 1 << 16];
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + 3 <= data.len() {
+            let h = hash(i);
+            let candidate = last_seen[h];
+            last_seen[h] = i;
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let limit = MAX_MATCH.min(data.len() - i);
+                let length = (0..limit)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                if length >= 3 {
+                    best = (length, i - candidate);
+                }
+            }
+        }
+
+        let (length, distance) = best;
+        if length == 0 {
+            write_symbol(&mut bits, data[i] as u32);
+            i += 1;
+            continue;
+        }
+
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|&base| base as usize <= length)
+            .unwrap();
+        write_symbol(&mut bits, 257 + code as u32);
+        bits.write(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+
+        let code = DISTANCE_BASE
+            .iter()
+            .rposition(|&base| base as usize <= distance)
+            .unwrap();
+        bits.write_code(code as u32, 5);
+        bits.write(
+            (distance - DISTANCE_BASE[code] as usize) as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+
+        for k in i + 1..(i + length).min(data.len().saturating_sub(2)) {
+            last_seen[hash(k)] = k;
+        }
+        i += length;
+    }
+    write_symbol(&mut bits, 256);
+
+    let mut stream = bits.finish();
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// 5x7 glyphs for the printable ASCII range, one byte per row from the top,
+/// with the leftmost pixel in bit 4.
+pub(crate) const GLYPHS: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x00, 0x0D, 0x12, 0x00, 0x00], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the fixed Huffman blocks `zlib` writes, checking the framing.
+    fn inflate(stream: &[u8]) -> Vec<u8> {
+        assert_eq!(u16::from_be_bytes([stream[0], stream[1]]) % 31, 0);
+        let mut position = 16;
+        let mut bit = |count: u32| {
+            let mut value = 0;
+            for i in 0..count {
+                let byte = stream[position / 8];
+                value |= ((byte >> (position % 8)) as u32 & 1) << i;
+                position += 1;
+            }
+            value
+        };
+
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = bit(1);
+            assert_eq!(bit(2), 1, "only fixed Huffman blocks are written");
+            loop {
+                let mut code = 0;
+                let mut length = 0;
+                let symbol = loop {
+                    code = code << 1 | bit(1);
+                    length += 1;
+                    match (length, code) {
+                        (7, 0..=0x17) => break 256 + code,
+                        (8, 0x30..=0xBF) => break code - 0x30,
+                        (8, 0xC0..=0xC7) => break 280 + code - 0xC0,
+                        (9, 0x190..=0x1FF) => break 144 + code - 0x190,
+                        _ => assert!(length < 9, "invalid code"),
+                    }
+                };
+                match symbol {
+                    0..=255 => out.push(symbol as u8),
+                    256 => break,
+                    _ => {
+                        let index = symbol as usize - 257;
+                        let length =
+                            LENGTH_BASE[index] as usize + bit(LENGTH_EXTRA[index] as u32) as usize;
+                        let code = (0..5).fold(0, |code, _| code << 1 | bit(1)) as usize;
+                        let distance = DISTANCE_BASE[code] as usize
+                            + bit(DISTANCE_EXTRA[code] as u32) as usize;
+                        for _ in 0..length {
+                            out.push(out[out.len() - distance]);
+                        }
+                    }
+                }
+            }
+            if last == 1 {
+                break;
+            }
+        }
+
+        let checksum = &stream[stream.len() - 4..];
+        assert_eq!(checksum, adler32(&out).to_be_bytes());
+        out
+    }
+
+    /// The chunks of `png`, checking the signature and every CRC.
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let body = &rest[4..8 + length];
+            let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+            assert_eq!(crc, crc32(body));
+            chunks.push((body[..4].try_into().unwrap(), body[4..].to_vec()));
+            rest = &rest[12 + length..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn zlib_round_trips() {
+        let mut noise = 0x1234_5678u32;
+        let mut data: Vec<u8> = (0..2000)
+            .map(|_| {
+                noise ^= noise << 13;
+                noise ^= noise >> 17;
+                noise ^= noise << 5;
+                noise as u8
+            })
+            .collect();
+        data.extend([7; 1000]);
+        data.extend_from_slice(b"abcabcabcabcabcabc");
+        data.extend_from_within(..600);
+
+        for input in [&b""[..], b"a", b"ab", b"aaaa", &data] {
+            assert_eq!(inflate(&zlib(input)), input);
+        }
+        assert!(zlib(&data).len() < data.len());
+    }
+
+    #[test]
+    fn renders_a_valid_png() {
+        let mut canvas = AsciiCanvas::new();
+        canvas
+            .rect([0.0, 0.0].into(), [4.0, 2.0].into())
+            .text([0.0, 0.0].into(), "hi");
+        let options = PngOptions::default();
+        let chunks = chunks(&render(&canvas, &options));
+
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        let header = &chunks[0].1;
+        let width = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        assert_eq!((width, height), (5 * 12, 3 * 18));
+        assert_eq!(inflate(&chunks[1].1).len(), (width * 3 + 1) * height);
+    }
+
+    #[test]
+    fn empty_output_is_a_single_background_pixel() {
+        let mut canvas = AsciiCanvas::new();
+        let empty = PngOptions::default();
+        canvas.text([0.0, 0.0].into(), "x");
+        let flat = PngOptions {
+            cell_height: 0,
+            ..PngOptions::default()
+        };
+
+        for png in [render(&AsciiCanvas::new(), &empty), render(&canvas, &flat)] {
+            let chunks = chunks(&png);
+            assert_eq!(&chunks[0].1[..8], [0, 0, 0, 1, 0, 0, 0, 1]);
+            let (r, g, b) = empty.background.to_rgb();
+            assert_eq!(inflate(&chunks[1].1), [0, r, g, b]);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// An entry of the 256 color palette.
+    Indexed(u8),
+    /// A 24 bit truecolor value.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Approximate sRGB value, using the xterm defaults for palette colors.
+    pub(crate) fn to_rgb(self) -> (u8, u8, u8) {
+        const ANSI: [(u8, u8, u8); 16] = [
+            (0, 0, 0),
+            (205, 0, 0),
+            (0, 205, 0),
+            (205, 205, 0),
+            (0, 0, 238),
+            (205, 0, 205),
+            (0, 205, 205),
+            (229, 229, 229),
+            (127, 127, 127),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (92, 92, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ];
+        let index = match self {
+            Color::Rgb(r, g, b) => return (r, g, b),
+            Color::Indexed(index) => index,
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+        };
+        match index {
+            0..=15 => ANSI[index as usize],
+            16..=231 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let i = index - 16;
+                (level(i / 36), level(i / 6 % 6), level(i % 6))
+            }
+            _ => {
+                let gray = 8 + (index - 232) * 10;
+                (gray, gray, gray)
+            }
+        }
+    }
+
+    /// CSS hex notation, e.g. `#ff8000`.
+    pub(crate) fn to_hex(self) -> String {
+        let (r, g, b) = self.to_rgb();
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+
+    fn push_sgr(self, out: &mut String, background: bool) {
+        let base = if background { 40 } else { 30 };
+        let extended = if background { 48 } else { 38 };
+        match self {
+            Color::Indexed(index) => write!(out, ";{extended};5;{index}"),
+            Color::Rgb(r, g, b) => write!(out, ";{extended};2;{r};{g};{b}"),
+            Color::Black => write!(out, ";{base}"),
+            Color::Red => write!(out, ";{}", base + 1),
+            Color::Green => write!(out, ";{}", base + 2),
+            Color::Yellow => write!(out, ";{}", base + 3),
+            Color::Blue => write!(out, ";{}", base + 4),
+            Color::Magenta => write!(out, ";{}", base + 5),
+            Color::Cyan => write!(out, ";{}", base + 6),
+            Color::White => write!(out, ";{}", base + 7),
+            Color::BrightBlack => write!(out, ";{}", base + 60),
+            Color::BrightRed => write!(out, ";{}", base + 61),
+            Color::BrightGreen => write!(out, ";{}", base + 62),
+            Color::BrightYellow => write!(out, ";{}", base + 63),
+            Color::BrightBlue => write!(out, ";{}", base + 64),
+            Color::BrightMagenta => write!(out, ";{}", base + 65),
+            Color::BrightCyan => write!(out, ";{}", base + 66),
+            Color::BrightWhite => write!(out, ";{}", base + 67),
+        }
+        .expect("writing to a String cannot fail");
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// The SGR escape sequence that switches a terminal from any state to this style.
+    pub(crate) fn sgr(&self) -> String {
+        let mut out = String::from("\x1B[0");
+        if self.bold {
+            out.push_str(";1");
+        }
+        if self.dim {
+            out.push_str(";2");
+        }
+        if self.underline {
+            out.push_str(";4");
+        }
+        if let Some(fg) = self.fg {
+            fg.push_sgr(&mut out, false);
+        }
+        if let Some(bg) = self.bg {
+            bg.push_sgr(&mut out, true);
+        }
+        out.push('m');
+        out
+    }
+}
+
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
+        Style::default().fg(color)
+    }
+}
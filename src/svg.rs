@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::{unicode, AsciiCanvas, Cell, CellRect, Color, Style, WIDE_CONTINUATION};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Width of a cell in SVG user units.
+    pub cell_width: f32,
+    /// Height of a cell in SVG user units.
+    pub cell_height: f32,
+    pub font_size: f32,
+    pub font_family: String,
+    /// Color of cells that have no foreground color of their own.
+    pub foreground: Color,
+    pub background: Option<Color>,
+    /// Draw the rects recorded by an `AsciiDrawer` as `<rect>` elements
+    /// instead of as border characters. Ignored when exporting a bare canvas.
+    pub vector_rects: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            cell_width: 10.0,
+            cell_height: 20.0,
+            font_size: 16.0,
+            font_family: "monospace".to_string(),
+            foreground: Color::Black,
+            background: Some(Color::BrightWhite),
+            vector_rects: false,
+        }
+    }
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn text_attributes(style: &Style) -> String {
+    let mut attributes = String::new();
+    if let Some(fg) = style.fg {
+        write!(attributes, r#" fill="{}""#, fg.to_hex()).unwrap();
+    }
+    if style.bold {
+        attributes.push_str(r#" font-weight="bold""#);
+    }
+    if style.dim {
+        attributes.push_str(r#" opacity="0.5""#);
+    }
+    if style.underline {
+        attributes.push_str(r#" text-decoration="underline""#);
+    }
+    attributes
+}
+
+pub(crate) fn render(
+    canvas: &AsciiCanvas,
+    options: &SvgOptions,
+    rects: &[(CellRect, Style)],
+) -> String {
+    let (cw, ch) = (options.cell_width, options.cell_height);
+    let frame = canvas.frame();
+
+    // Cell (x, y) covers [x, x + 1] * cw horizontally and, as +y points up,
+    // [-y, -y + 1] * ch vertically.
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        frame.left as f32 * cw,
+        -frame.top as f32 * ch,
+        frame.width() as f32 * cw,
+        frame.height() as f32 * ch,
+        frame.width() as f32 * cw,
+        frame.height() as f32 * ch,
+    )
+    .unwrap();
+
+    if let Some(background) = options.background {
+        writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            frame.left as f32 * cw,
+            -frame.top as f32 * ch,
+            frame.width() as f32 * cw,
+            frame.height() as f32 * ch,
+            background.to_hex(),
+        )
+        .unwrap();
+    }
+
+    let mut replaced = HashSet::new();
+    for (rect, _) in rects {
+        for x in rect.left..=rect.right {
+            replaced.insert((x, rect.bottom));
+            replaced.insert((x, rect.top));
+        }
+        for y in rect.bottom..=rect.top {
+            replaced.insert((rect.left, y));
+            replaced.insert((rect.right, y));
+        }
+    }
+
+    let rows = canvas.rows();
+    let cell_position =
+        |column: usize, row: usize| (frame.left + column as i32, frame.top - row as i32);
+
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column, cell) in row.iter().enumerate() {
+            if let Some(bg) = cell.style.bg {
+                let (x, y) = cell_position(column, row_index);
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{cw}" height="{ch}" fill="{}"/>"#,
+                    x as f32 * cw,
+                    -y as f32 * ch,
+                    bg.to_hex(),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    writeln!(
+        svg,
+        r#"<g font-family="{}" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">"#,
+        escape_xml(&options.font_family),
+        options.font_size,
+        options.foreground.to_hex(),
+    )
+    .unwrap();
+
+    let hidden = |cell: &Cell, x: i32, y: i32| {
+        cell.ch == ' '
+            || cell.ch == WIDE_CONTINUATION
+            || (cell.strokes != 0 && replaced.contains(&(x, y)))
+    };
+    // Characters that fill exactly one cell can share a `<text>` element.
+    let single = |cell: &Cell| cell.cluster.is_none() && unicode::char_width(cell.ch) == 1;
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let mut column = 0;
+        while column < row.len() {
+            let (x, y) = cell_position(column, row_index);
+            let cell = &row[column];
+            if hidden(cell, x, y) {
+                column += 1;
+                continue;
+            }
+
+            // Group a run of visible cells sharing a style into one element,
+            // placing every character on its own cell center. Wide characters
+            // and graphemes made of several characters get one each.
+            let mut xs = Vec::new();
+            let mut text = String::new();
+            if single(cell) {
+                while column < row.len() {
+                    let (x, y) = cell_position(column, row_index);
+                    let next = &row[column];
+                    if hidden(next, x, y) || !single(next) || next.style != cell.style {
+                        break;
+                    }
+                    xs.push(format!("{}", (x as f32 + 0.5) * cw));
+                    text.push(next.ch);
+                    column += 1;
+                }
+            } else {
+                cell.push_to(&mut text);
+                let width = unicode::cluster_width(&text) as f32;
+                xs.push(format!("{}", (x as f32 + width / 2.0) * cw));
+                column += 1;
+            }
+
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}"{}>{}</text>"#,
+                xs.join(" "),
+                (-y as f32 + 0.5) * ch,
+                text_attributes(&cell.style),
+                escape_xml(&text),
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</g>\n");
+
+    for (rect, style) in rects {
+        let stroke = style.fg.unwrap_or(options.foreground);
+        writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            (rect.left as f32 + 0.5) * cw,
+            (-rect.top as f32 + 0.5) * ch,
+            (rect.right - rect.left) as f32 * cw,
+            (rect.top - rect.bottom) as f32 * ch,
+            stroke.to_hex(),
+            cw.min(ch) * 0.15,
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,57 @@
+//! Asks the terminal how big it is, without pulling in a crate for it.
+
+/// Columns and rows of the terminal attached to stdout, stderr or stdin,
+/// the first one that is a terminal.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+pub(crate) fn window_size() -> Option<(u32, u32)> {
+    use std::os::raw::{c_int, c_ulong};
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Winsize {
+        rows: u16,
+        columns: u16,
+        x_pixels: u16,
+        y_pixels: u16,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    [1, 2, 0].into_iter().find_map(|fd| {
+        let mut size = Winsize::default();
+        // SAFETY: TIOCGWINSZ only writes a `struct winsize`, which `Winsize` mirrors.
+        let result = unsafe { ioctl(fd, TIOCGWINSZ, &mut size as *mut Winsize) };
+        (result == 0 && size.columns > 0 && size.rows > 0)
+            .then(|| (size.columns.into(), size.rows.into()))
+    })
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+pub(crate) fn window_size() -> Option<(u32, u32)> {
+    None
+}
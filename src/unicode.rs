@@ -0,0 +1,237 @@
+//! Just enough Unicode segmentation and East Asian width to put text on a
+//! grid of terminal cells.
+
+/// Characters drawn over the one before them rather than in a cell of
+/// their own: combining marks, joiners, variation selectors and emoji
+/// modifiers.
+fn is_zero_width(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x05BF
+            | 0x05C1..=0x05C2
+            | 0x05C4..=0x05C5
+            | 0x05C7
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0670
+            | 0x06D6..=0x06DC
+            | 0x06DF..=0x06E4
+            | 0x06E7..=0x06E8
+            | 0x06EA..=0x06ED
+            | 0x0900..=0x0903
+            | 0x093A..=0x093C
+            | 0x093E..=0x094F
+            | 0x0951..=0x0957
+            | 0x0962..=0x0963
+            | 0x0E31
+            | 0x0E34..=0x0E3A
+            | 0x0E47..=0x0E4E
+            | 0x1160..=0x11FF
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200B..=0x200F
+            | 0x20D0..=0x20FF
+            | 0x302A..=0x302F
+            | 0x3099..=0x309A
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xFEFF
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0000..=0xE007F
+            | 0xE0100..=0xE01EF
+    )
+}
+
+/// Characters that terminals display two cells wide: CJK ideographs, kana,
+/// Hangul syllables, fullwidth forms and emoji.
+fn is_wide(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xA960..=0xA97F
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE10..=0xFE19
+            | 0xFE30..=0xFE6F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x16FE0..=0x18AFF
+            | 0x1B000..=0x1B2FF
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F200..=0x1F251
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x2FFFD
+            | 0x30000..=0x3FFFD
+    )
+}
+
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+/// Number of cells `ch` takes on its own: 0, 1 or 2.
+pub(crate) fn char_width(ch: char) -> usize {
+    if is_zero_width(ch) {
+        0
+    } else if is_wide(ch) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Splits `text` into the groups of characters that are displayed together,
+/// a base character followed by its marks, joined emoji sequences and flags.
+pub(crate) fn graphemes(text: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    let mut regional_indicators = 0;
+
+    for (index, ch) in text.char_indices() {
+        let extends = match previous {
+            None => false,
+            Some(ZERO_WIDTH_JOINER) => true,
+            Some(_) if is_zero_width(ch) => true,
+            Some(_) => is_regional_indicator(ch) && regional_indicators % 2 == 1,
+        };
+        if !extends && index > start {
+            clusters.push(&text[start..index]);
+            start = index;
+        }
+        regional_indicators = if is_regional_indicator(ch) {
+            regional_indicators + 1
+        } else {
+            0
+        };
+        previous = Some(ch);
+    }
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Number of cells a cluster returned by [`graphemes`] takes, 1 or 2.
+pub(crate) fn cluster_width(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+    let flag =
+        is_regional_indicator(first) && chars.clone().next().is_some_and(is_regional_indicator);
+    if flag || cluster.contains(EMOJI_PRESENTATION) {
+        return 2;
+    }
+    char_width(first).max(1)
+}
+
+/// Number of cells `text` takes on a single line.
+pub(crate) fn text_width(text: &str) -> usize {
+    graphemes(text).into_iter().map(cluster_width).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combining_marks_stay_with_their_base() {
+        assert_eq!(
+            graphemes("e\u{301}x\u{308}\u{304}"),
+            ["e\u{301}", "x\u{308}\u{304}"]
+        );
+        assert_eq!(cluster_width("e\u{301}"), 1);
+        assert_eq!(text_width("e\u{301}x\u{308}\u{304}"), 2);
+    }
+
+    #[test]
+    fn joined_emoji_and_modifiers_form_one_wide_cluster() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(graphemes(&format!("{family}a")), [family, "a"]);
+        assert_eq!(cluster_width(family), 2);
+
+        let thumbs_up = "\u{1F44D}\u{1F3FD}";
+        assert_eq!(graphemes(thumbs_up), [thumbs_up]);
+        assert_eq!(cluster_width(thumbs_up), 2);
+    }
+
+    #[test]
+    fn regional_indicators_pair_into_flags() {
+        let (france, germany) = ("\u{1F1EB}\u{1F1F7}", "\u{1F1E9}\u{1F1EA}");
+        assert_eq!(graphemes(&format!("{france}{germany}")), [france, germany]);
+        assert_eq!(cluster_width(france), 2);
+        assert_eq!(
+            graphemes("\u{1F1EB}\u{1F1F7}\u{1F1E9}"),
+            [france, "\u{1F1E9}"]
+        );
+    }
+
+    #[test]
+    fn emoji_presentation_selector_makes_a_cluster_wide() {
+        assert_eq!(cluster_width("\u{2764}"), 1);
+        assert_eq!(graphemes("\u{2764}\u{FE0F}"), ["\u{2764}\u{FE0F}"]);
+        assert_eq!(cluster_width("\u{2764}\u{FE0F}"), 2);
+    }
+
+    #[test]
+    fn cjk_takes_two_cells() {
+        assert_eq!(graphemes("漢字a"), ["漢", "字", "a"]);
+        assert_eq!(char_width('漢'), 2);
+        assert_eq!(cluster_width("字"), 2);
+        assert_eq!(text_width("漢字a"), 5);
+        assert_eq!(text_width(""), 0);
+    }
+}